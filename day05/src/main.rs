@@ -1,10 +1,13 @@
-use std::ops::Range;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+};
 
 use nom::{
     bytes::complete::tag,
-    character::complete::{alpha0, char, newline, space1, u64},
+    character::complete::{alpha1, newline, space1, u64},
     multi::{separated_list0, separated_list1},
-    sequence::{preceded, tuple},
+    sequence::{preceded, separated_pair, tuple},
     IResult,
 };
 
@@ -15,23 +18,156 @@ fn read_input() -> String {
 
 type Seeds = Vec<u64>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Direction {
     Sub(u64),
     Add(u64),
 }
 
-#[derive(Debug)]
+impl Direction {
+    fn from_offset(offset: i64) -> Self {
+        if offset < 0 {
+            Direction::Sub(offset.unsigned_abs())
+        } else {
+            Direction::Add(offset as u64)
+        }
+    }
+
+    fn offset(&self) -> i64 {
+        match self {
+            Direction::Sub(d) => -(*d as i64),
+            Direction::Add(d) => *d as i64,
+        }
+    }
+
+    fn apply(&self, value: u64) -> u64 {
+        match self {
+            Direction::Sub(d) => value - d,
+            Direction::Add(d) => value + d,
+        }
+    }
+
+    fn inverse(&self) -> Self {
+        match self {
+            Direction::Sub(d) => Direction::Add(*d),
+            Direction::Add(d) => Direction::Sub(*d),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Direction::Sub(d) => write!(f, "-{d}"),
+            Direction::Add(d) => write!(f, "+{d}"),
+        }
+    }
+}
+
+/// A half-open range of values. Unlike `Range<u64>` its bounds are plain fields, so nothing
+/// ever has to iterate over it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Interval {
+    start: u64,
+    end: u64,
+}
+
+impl Interval {
+    fn new(start: u64, end: u64) -> Self {
+        Interval { start, end }
+    }
+
+    fn with_len(start: u64, len: u64) -> Self {
+        Interval::new(start, start + len)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    fn contains(&self, value: u64) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    fn intersect(&self, other: &Interval) -> Option<Interval> {
+        let interval = Interval::new(self.start.max(other.start), self.end.min(other.end));
+
+        (!interval.is_empty()).then_some(interval)
+    }
+
+    /// The parts of this interval below and from `value`, either of which may be empty.
+    fn split_at(&self, value: u64) -> (Interval, Interval) {
+        let value = value.clamp(self.start, self.end.max(self.start));
+
+        (
+            Interval::new(self.start, value),
+            Interval::new(value, self.end),
+        )
+    }
+
+    fn shift(&self, direction: &Direction) -> Interval {
+        Interval::new(direction.apply(self.start), direction.apply(self.end))
+    }
+}
+
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+#[derive(Debug, Clone)]
 struct Displacement {
-    range: Range<u64>,
+    range: Interval,
     direction: Direction,
 }
 
-#[derive(Debug)]
+impl Displacement {
+    /// The displacement mapping this displacement's destination range back onto its source.
+    fn inverse(&self) -> Self {
+        Displacement {
+            range: self.range.shift(&self.direction),
+            direction: self.direction.inverse(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 struct Map {
+    from: String,
+    to: String,
     mappings: Vec<Displacement>,
 }
 
+#[derive(Debug)]
+enum MapIssue {
+    ZeroLength(usize),
+    Overlap(usize, usize),
+}
+
+impl Map {
+    /// Problems with this map's mappings, by their index in the map. Overlapping mappings are
+    /// still usable, the first one listed wins.
+    fn validate(&self) -> Vec<MapIssue> {
+        let mut issues = vec![];
+
+        for (index, mapping) in self.mappings.iter().enumerate() {
+            if mapping.range.is_empty() {
+                issues.push(MapIssue::ZeroLength(index));
+                continue;
+            }
+
+            for (other_index, other) in self.mappings.iter().enumerate().skip(index + 1) {
+                if mapping.range.intersect(&other.range).is_some() {
+                    issues.push(MapIssue::Overlap(index, other_index));
+                }
+            }
+        }
+
+        issues
+    }
+}
+
 fn seeds(input: &str) -> IResult<&str, Seeds> {
     let (extra, seeds) = preceded(tag("seeds: "), separated_list0(space1, u64))(input)?;
 
@@ -45,35 +181,181 @@ fn displacement(input: &str) -> IResult<&str, Displacement> {
     let direction = match source_start.cmp(&destination_start) {
         std::cmp::Ordering::Less => Direction::Add(destination_start - source_start),
         std::cmp::Ordering::Greater => Direction::Sub(source_start - destination_start),
-        std::cmp::Ordering::Equal => unreachable!("No ranges of length 0"),
+        std::cmp::Ordering::Equal => Direction::Add(0),
     };
 
-    let range = source_start..(source_start + range_length);
+    let range = Interval::with_len(source_start, range_length);
 
     Ok((extra, Displacement { range, direction }))
 }
 
-fn seed_map_name(input: &str) -> IResult<&str, String> {
-    let (extra, results) = separated_list0(char('-'), alpha0)(input)?;
-    let from = results.first().unwrap();
-    let to = results.last().unwrap();
+fn seed_map_name(input: &str) -> IResult<&str, (String, String)> {
+    let (extra, (from, to)) = separated_pair(alpha1, tag("-to-"), alpha1)(input)?;
 
-    Ok((extra, format!("{from}-to-{to}")))
+    Ok((extra, (from.to_string(), to.to_string())))
 }
 
 fn seed_map(input: &str) -> IResult<&str, Map> {
-    let (extra, (_, _, _, mappings)) = tuple((
+    let (extra, ((from, to), _, _, mappings)) = tuple((
         seed_map_name,
         tag(" map:"),
         newline,
         separated_list0(newline, displacement),
     ))(input)?;
 
-    let map = Map { mappings };
+    let map = Map { from, to, mappings };
 
     Ok((extra, map))
 }
 
+#[derive(Debug)]
+enum AlmanacError {
+    MissingPath { from: String, to: String },
+    Cycle(Vec<String>),
+    Ambiguous { category: String, next: Vec<String> },
+}
+
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlmanacError::MissingPath { from, to } => {
+                write!(f, "no chain of maps from '{from}' to '{to}'")
+            }
+            AlmanacError::Cycle(categories) => {
+                write!(f, "maps form a cycle: {}", categories.join(" -> "))
+            }
+            AlmanacError::Ambiguous { category, next } => write!(
+                f,
+                "more than one way on from '{category}': {}",
+                next.join(", ")
+            ),
+        }
+    }
+}
+
+/// The almanac's maps as a graph of categories, with every map an edge from its source category
+/// to its destination category.
+#[derive(Debug)]
+struct Almanac {
+    maps: HashMap<String, Vec<Map>>,
+}
+
+impl Almanac {
+    fn new(maps: Vec<Map>) -> Self {
+        let mut by_source: HashMap<String, Vec<Map>> = HashMap::new();
+
+        for map in maps {
+            by_source.entry(map.from.clone()).or_default().push(map);
+        }
+
+        Almanac { maps: by_source }
+    }
+
+    fn outgoing(&self, category: &str) -> &[Map] {
+        self.maps.get(category).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every category with a chain of maps into `to`, `to` included.
+    fn leading_to<'a>(&'a self, to: &'a str) -> HashSet<&'a str> {
+        let mut leading_to = HashSet::from([to]);
+        let mut queue = VecDeque::from([to]);
+
+        while let Some(category) = queue.pop_front() {
+            for map in self.maps.values().flatten() {
+                if map.to == category && leading_to.insert(&map.from) {
+                    queue.push_back(&map.from);
+                }
+            }
+        }
+
+        leading_to
+    }
+
+    /// A cycle among the categories in `relevant` that can be reached from `category` without
+    /// passing through `to`.
+    fn find_cycle<'a>(
+        &'a self,
+        category: &'a str,
+        to: &str,
+        relevant: &HashSet<&str>,
+        path: &mut Vec<&'a str>,
+        done: &mut HashSet<&'a str>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|&visited| visited == category) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|c| c.to_string()).collect();
+            cycle.push(category.to_string());
+
+            return Some(cycle);
+        }
+
+        if category == to || !done.insert(category) {
+            return None;
+        }
+
+        path.push(category);
+
+        let cycle = self
+            .outgoing(category)
+            .iter()
+            .filter(|map| relevant.contains(map.to.as_str()))
+            .find_map(|map| self.find_cycle(&map.to, to, relevant, path, done));
+
+        path.pop();
+
+        cycle
+    }
+
+    /// The maps that, applied in order, convert `from` into `to`. There has to be exactly one
+    /// such chain.
+    fn chain(&self, from: &str, to: &str) -> Result<Vec<Map>, AlmanacError> {
+        let leading_to = self.leading_to(to);
+
+        if !leading_to.contains(from) {
+            return Err(AlmanacError::MissingPath {
+                from: from.to_string(),
+                to: to.to_string(),
+            });
+        }
+
+        if from == to {
+            return Ok(vec![]);
+        }
+
+        // a cycle on the way would allow endlessly many chains
+        let mut done = HashSet::new();
+        if let Some(cycle) = self.find_cycle(from, to, &leading_to, &mut vec![], &mut done) {
+            return Err(AlmanacError::Cycle(cycle));
+        }
+
+        let mut chain = vec![];
+        let mut category = from;
+
+        while category != to {
+            let next: Vec<&Map> = self
+                .outgoing(category)
+                .iter()
+                .filter(|map| leading_to.contains(map.to.as_str()))
+                .collect();
+
+            let [map] = next[..] else {
+                return Err(AlmanacError::Ambiguous {
+                    category: category.to_string(),
+                    next: next.iter().map(|map| map.to.clone()).collect(),
+                });
+            };
+
+            chain.push(map.clone());
+            category = &map.to;
+        }
+
+        Ok(chain)
+    }
+
+    fn convert(&self, from: &str, to: &str, value: u64) -> Result<u64, AlmanacError> {
+        Ok(PiecewiseMap::compose(&self.chain(from, to)?).get(value))
+    }
+}
+
 fn whole_input(input: &str) -> IResult<&str, (Seeds, Vec<Map>)> {
     let (extra, (seeds, _, maps)) =
         tuple((seeds, tag("\n\n"), separated_list1(tag("\n\n"), seed_map)))(input)?;
@@ -101,7 +383,7 @@ fn part1(seeds: &[u64], maps: &[Map]) -> u64 {
             for mapping in &map.mappings {
                 let range = &mapping.range;
 
-                if range.contains(&step) {
+                if range.contains(step) {
                     match &mapping.direction {
                         Direction::Sub(d) => step -= d,
                         Direction::Add(d) => step += d,
@@ -118,70 +400,28 @@ fn part1(seeds: &[u64], maps: &[Map]) -> u64 {
     results.into_iter().min().unwrap()
 }
 
-type SeedRange = Range<u64>;
-
-fn update_range(range: &SeedRange, direction: &Direction) -> SeedRange {
-    let min = range.clone().min().unwrap();
-    let max = range.clone().max().unwrap();
-
-    match direction {
-        Direction::Sub(d) => (min - d)..(max + 1 - d),
-        Direction::Add(d) => (min + d)..(max + 1 + d),
-    }
-}
+type SeedRange = Interval;
 
 fn handle_map(seed_range: &SeedRange, map: &Map) -> Vec<SeedRange> {
-    let mut outputs = vec![];
-
-    let seed_range_min = seed_range.clone().min().unwrap();
-    let seed_range_max = seed_range.clone().max().unwrap();
+    if seed_range.is_empty() {
+        return vec![];
+    }
 
     for mapping in &map.mappings {
-        let mapping_range = &mapping.range;
-
-        let mapping_range_min = mapping_range.clone().min().unwrap();
-        let mapping_range_max = mapping_range.clone().max().unwrap();
-
-        // both
-        if mapping_range.contains(&seed_range_min) && mapping_range.contains(&seed_range_max) {
-            outputs.push(update_range(seed_range, &mapping.direction));
-            break;
-        }
-        // minimum overlapped
-        else if mapping_range.contains(&(seed_range.clone().min().unwrap())) {
-            outputs.push(update_range(
-                &(seed_range_min..mapping_range_max),
-                &mapping.direction,
-            ));
-
-            handle_map(&((mapping_range_max + 1)..(seed_range_max + 1)), map)
-                .into_iter()
-                .for_each(|x| outputs.push(x));
+        if let Some(overlap) = seed_range.intersect(&mapping.range) {
+            let (before, rest) = seed_range.split_at(overlap.start);
+            let (_, after) = rest.split_at(overlap.end);
 
-            break;
-        }
-        // maximum overlapped
-        else if mapping_range.contains(&(seed_range.clone().max().unwrap())) {
-            let new_overlapping =
-                update_range(&(mapping_range_min..seed_range_max), &mapping.direction);
-            outputs.push(new_overlapping);
-
-            let not_overlapping = seed_range_min..mapping_range_min;
-            let new_not_overlapping = handle_map(&not_overlapping, map);
+            // the parts outside this mapping may still be hit by another one
+            let mut outputs = vec![overlap.shift(&mapping.direction)];
+            outputs.extend(handle_map(&before, map));
+            outputs.extend(handle_map(&after, map));
 
-            new_not_overlapping
-                .into_iter()
-                .for_each(|x| outputs.push(x));
-
-            break;
+            return outputs;
         }
     }
 
-    if outputs.is_empty() {
-        outputs.push(seed_range.clone());
-    }
-
-    outputs
+    vec![*seed_range]
 }
 
 fn handle_seed_ranges(seed_ranges: &[SeedRange], maps: &[Map]) -> Vec<SeedRange> {
@@ -202,18 +442,264 @@ fn handle_seed_ranges(seed_ranges: &[SeedRange], maps: &[Map]) -> Vec<SeedRange>
     handle_seed_ranges(&new_seed_ranges, remaining_maps)
 }
 
-fn part2(seeds: &[u64], maps: &[Map]) -> u64 {
-    let seed_ranges: Vec<Range<u64>> = seeds
+fn seed_ranges(seeds: &[u64]) -> Vec<SeedRange> {
+    seeds
         .windows(2)
         .step_by(2)
-        .map(|x| x[0]..(x[0] + x[1]))
-        .collect();
+        .map(|x| Interval::with_len(x[0], x[1]))
+        .collect()
+}
 
-    let final_locations = handle_seed_ranges(&seed_ranges, maps);
+fn part2(seeds: &[u64], maps: &[Map]) -> u64 {
+    let final_locations = handle_seed_ranges(&seed_ranges(seeds), maps);
 
     final_locations
         .into_iter()
-        .map(|range| range.min().unwrap())
+        .map(|range| range.start)
+        .min()
+        .unwrap()
+}
+
+/// A chain of maps collapsed into a single function. Segments are sorted by start, do not
+/// overlap, are never empty and never have an offset of zero; values outside every segment map
+/// to themselves.
+#[derive(Debug, Clone, Default)]
+struct PiecewiseMap {
+    segments: Vec<Displacement>,
+}
+
+impl PiecewiseMap {
+    /// Normalise a single almanac map. Where mappings overlap the first one listed wins, just
+    /// like in `part1`.
+    fn from_map(map: &Map) -> Self {
+        let mut breakpoints: Vec<u64> = map
+            .mappings
+            .iter()
+            .flat_map(|mapping| [mapping.range.start, mapping.range.end])
+            .collect();
+        breakpoints.sort_unstable();
+        breakpoints.dedup();
+
+        let pieces = breakpoints.windows(2).filter_map(|window| {
+            let piece = Interval::new(window[0], window[1]);
+
+            map.mappings
+                .iter()
+                .find(|mapping| mapping.range.contains(piece.start))
+                .map(|mapping| (piece, mapping.direction.offset()))
+        });
+
+        Self::from_pieces(pieces)
+    }
+
+    /// Compose a chain of maps, applied left to right, into a single map.
+    fn compose(maps: &[Map]) -> Self {
+        maps.iter().fold(PiecewiseMap::default(), |composed, map| {
+            composed.then(&PiecewiseMap::from_map(map))
+        })
+    }
+
+    /// The map that first applies `self` and then `next`.
+    fn then(&self, next: &PiecewiseMap) -> Self {
+        let pieces = self.cover().into_iter().flat_map(|(range, offset)| {
+            let image = shift(&range, offset);
+
+            next.split(&image)
+                .into_iter()
+                .map(move |(part, next_offset)| (shift(&part, -offset), offset + next_offset))
+        });
+
+        Self::from_pieces(pieces)
+    }
+
+    /// Build a map from sorted, non-overlapping pieces, dropping identity pieces and merging
+    /// neighbours that share an offset.
+    fn from_pieces(pieces: impl IntoIterator<Item = (SeedRange, i64)>) -> Self {
+        let mut segments: Vec<Displacement> = vec![];
+
+        for (range, offset) in pieces {
+            if range.is_empty() || offset == 0 {
+                continue;
+            }
+
+            let direction = Direction::from_offset(offset);
+
+            match segments.last_mut() {
+                Some(last) if last.range.end == range.start && last.direction == direction => {
+                    last.range.end = range.end;
+                }
+                _ => segments.push(Displacement { range, direction }),
+            }
+        }
+
+        PiecewiseMap { segments }
+    }
+
+    /// All segments together with the identity gaps between them, covering every `u64` except
+    /// `u64::MAX`.
+    fn cover(&self) -> Vec<(SeedRange, i64)> {
+        self.split(&Interval::new(0, u64::MAX))
+    }
+
+    /// Split `range` along the segment boundaries, pairing each part with its offset.
+    fn split(&self, range: &SeedRange) -> Vec<(SeedRange, i64)> {
+        let mut parts = vec![];
+        let mut start = range.start;
+
+        let first = self
+            .segments
+            .partition_point(|segment| segment.range.end <= start);
+
+        for segment in &self.segments[first..] {
+            if start >= range.end {
+                break;
+            }
+
+            if segment.range.start > start {
+                let gap_end = segment.range.start.min(range.end);
+                parts.push((Interval::new(start, gap_end), 0));
+                start = gap_end;
+            }
+
+            if start < range.end {
+                let end = segment.range.end.min(range.end);
+                parts.push((Interval::new(start, end), segment.direction.offset()));
+                start = end;
+            }
+        }
+
+        if start < range.end {
+            parts.push((Interval::new(start, range.end), 0));
+        }
+
+        parts
+    }
+
+    fn get(&self, value: u64) -> u64 {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.range.end <= value);
+
+        match self.segments.get(index) {
+            Some(segment) if segment.range.contains(value) => segment.direction.apply(value),
+            _ => value,
+        }
+    }
+
+    fn get_range(&self, range: &SeedRange) -> Vec<SeedRange> {
+        self.split(range)
+            .into_iter()
+            .map(|(part, offset)| shift(&part, offset))
+            .collect()
+    }
+
+    /// All values that this map sends into `range`, sorted and merged.
+    fn preimage(&self, range: &SeedRange) -> Vec<SeedRange> {
+        // values outside every segment are mapped onto themselves
+        let unmapped = self
+            .split(range)
+            .into_iter()
+            .filter(|(_, offset)| *offset == 0)
+            .map(|(part, _)| part);
+
+        let mapped = self.segments.iter().filter_map(|segment| {
+            let inverse = segment.inverse();
+
+            inverse
+                .range
+                .intersect(range)
+                .map(|part| part.shift(&inverse.direction))
+        });
+
+        merge_ranges(unmapped.chain(mapped).collect())
+    }
+}
+
+impl fmt::Display for PiecewiseMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for segment in &self.segments {
+            let image = shift(&segment.range, segment.direction.offset());
+
+            writeln!(
+                f,
+                "{:?} -> {:?} ({})",
+                segment.range, image, segment.direction
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn shift(range: &SeedRange, offset: i64) -> SeedRange {
+    range.shift(&Direction::from_offset(offset))
+}
+
+fn merge_ranges(mut ranges: Vec<SeedRange>) -> Vec<SeedRange> {
+    ranges.sort_unstable_by_key(|range| range.start);
+
+    let mut merged: Vec<SeedRange> = vec![];
+
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+
+    merged
+}
+
+/// Trace a range of locations back through every map to the values it started from.
+fn trace_back(range: &SeedRange, maps: &[Map]) -> Vec<SeedRange> {
+    let normalised: Vec<PiecewiseMap> = maps.iter().map(PiecewiseMap::from_map).collect();
+
+    normalised.iter().rev().fold(vec![*range], |ranges, map| {
+        merge_ranges(
+            ranges
+                .iter()
+                .flat_map(|range| map.preimage(range))
+                .collect(),
+        )
+    })
+}
+
+/// The seeds from the almanac that end up somewhere in `locations`.
+fn seeds_for_locations(seeds: &[u64], maps: &[Map], locations: &SeedRange) -> Vec<u64> {
+    let origins = trace_back(locations, maps);
+
+    seeds
+        .iter()
+        .filter(|seed| origins.iter().any(|origin| origin.contains(**seed)))
+        .copied()
+        .collect()
+}
+
+/// The parts of the almanac's seed ranges that end up somewhere in `locations`.
+fn seed_ranges_for_locations(seeds: &[u64], maps: &[Map], locations: &SeedRange) -> Vec<SeedRange> {
+    let origins = trace_back(locations, maps);
+
+    merge_ranges(
+        seed_ranges(seeds)
+            .iter()
+            .flat_map(|seed_range| {
+                origins
+                    .iter()
+                    .filter_map(move |origin| seed_range.intersect(origin))
+            })
+            .collect(),
+    )
+}
+
+fn part1_composed(seeds: &[u64], composed: &PiecewiseMap) -> u64 {
+    seeds.iter().map(|seed| composed.get(*seed)).min().unwrap()
+}
+
+fn part2_composed(seeds: &[u64], composed: &PiecewiseMap) -> u64 {
+    seed_ranges(seeds)
+        .iter()
+        .flat_map(|range| composed.get_range(range))
+        .map(|range| range.start)
         .min()
         .unwrap()
 }
@@ -222,6 +708,271 @@ fn main() {
     let lines = read_input();
     let (seeds, maps) = parse(&lines);
 
-    println!("part1: {}", part1(&seeds, &maps));
-    println!("part2: {}", part2(&seeds, &maps));
+    for map in &maps {
+        for issue in map.validate() {
+            let name = format!("{}-to-{}", map.from, map.to);
+
+            match issue {
+                MapIssue::ZeroLength(index) => {
+                    println!("warning: {name} mapping {index} has length 0")
+                }
+                MapIssue::Overlap(first, second) => {
+                    println!("warning: {name} mappings {first} and {second} overlap, using {first}")
+                }
+            }
+        }
+    }
+
+    let almanac = Almanac::new(maps);
+
+    let args: Vec<String> = std::env::args().collect();
+
+    if let Some(index) = args.iter().position(|arg| arg == "--convert") {
+        let [from, to, value] = &args[index + 1..] else {
+            println!("usage: --convert <from> <to> <value>");
+            return;
+        };
+
+        let value: u64 = match value.parse() {
+            Ok(value) => value,
+            Err(err) => {
+                println!("error: {err}");
+                return;
+            }
+        };
+
+        match almanac.convert(from, to, value) {
+            Ok(converted) => println!("{from} {value} is {to} {converted}"),
+            Err(err) => println!("error: {err}"),
+        }
+
+        return;
+    }
+
+    let maps = match almanac.chain("seed", "location") {
+        Ok(maps) => maps,
+        Err(err) => {
+            println!("error: {err}");
+            return;
+        }
+    };
+
+    if args.iter().any(|arg| arg == "--composed") {
+        let composed = PiecewiseMap::compose(&maps);
+
+        print!("{composed}");
+        println!("part1: {}", part1_composed(&seeds, &composed));
+        println!("part2: {}", part2_composed(&seeds, &composed));
+
+        return;
+    }
+
+    let lowest_single = part1(&seeds, &maps);
+    let lowest_range = part2(&seeds, &maps);
+
+    println!("part1: {}", lowest_single);
+    println!("part2: {}", lowest_range);
+
+    if args.iter().any(|arg| arg == "--inverse") {
+        println!(
+            "seeds reaching {lowest_single}: {:?}",
+            seeds_for_locations(&seeds, &maps, &Interval::with_len(lowest_single, 1))
+        );
+        println!(
+            "seed ranges reaching {lowest_range}: {:?}",
+            seed_ranges_for_locations(&seeds, &maps, &Interval::with_len(lowest_range, 1))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+    fn almanac(names: &[&str]) -> Almanac {
+        let maps = names
+            .iter()
+            .map(|name| seed_map(&format!("{name} map:\n10 0 5")).unwrap().1)
+            .collect();
+
+        Almanac::new(maps)
+    }
+
+    fn map(mappings: &str) -> Map {
+        seed_map(&format!("seed-to-soil map:\n{mappings}"))
+            .unwrap()
+            .1
+    }
+
+    fn route(chain: &[Map]) -> Vec<String> {
+        chain
+            .iter()
+            .map(|map| format!("{}-to-{}", map.from, map.to))
+            .collect()
+    }
+
+    #[test]
+    fn chain_ignores_branches_that_lead_elsewhere() {
+        let almanac = almanac(&["seed-to-soil", "seed-to-light", "soil-to-water"]);
+
+        assert_eq!(
+            route(&almanac.chain("seed", "water").unwrap()),
+            ["seed-to-soil", "soil-to-water"]
+        );
+        assert_eq!(
+            route(&almanac.chain("seed", "light").unwrap()),
+            ["seed-to-light"]
+        );
+        assert_eq!(almanac.convert("seed", "water", 3).unwrap(), 13);
+    }
+
+    #[test]
+    fn chain_rejects_missing_ambiguous_and_cyclic_paths() {
+        let almanac = almanac(&[
+            "seed-to-soil",
+            "seed-to-light",
+            "soil-to-water",
+            "light-to-water",
+            "water-to-heat",
+            "heat-to-water",
+            "heat-to-humidity",
+        ]);
+
+        assert!(matches!(
+            almanac.chain("water", "seed"),
+            Err(AlmanacError::MissingPath { .. })
+        ));
+        assert!(matches!(
+            almanac.chain("seed", "water"),
+            Err(AlmanacError::Ambiguous { .. })
+        ));
+        assert!(matches!(
+            almanac.chain("water", "humidity"),
+            Err(AlmanacError::Cycle(_))
+        ));
+    }
+
+    #[test]
+    fn identity_mapping_keeps_values() {
+        let maps = [map("50 50 10\n0 50 10")];
+
+        assert_eq!(maps[0].mappings[0].direction, Direction::Add(0));
+        assert_eq!(part1(&[55], &maps), 55);
+        assert_eq!(part2(&[50, 10], &maps), 50);
+
+        let composed = PiecewiseMap::compose(&maps);
+        assert_eq!(composed.get(55), 55);
+        assert_eq!(composed.get(60), 60);
+    }
+
+    #[test]
+    fn validate_reports_zero_length_mappings() {
+        let issues = map("50 98 2\n52 50 0").validate();
+
+        assert!(matches!(issues[..], [MapIssue::ZeroLength(1)]));
+    }
+
+    #[test]
+    fn overlapping_mappings_use_the_first_one_listed() {
+        let maps = [map("100 0 10\n200 5 10")];
+
+        assert!(matches!(maps[0].validate()[..], [MapIssue::Overlap(0, 1)]));
+
+        let composed = PiecewiseMap::compose(&maps);
+
+        for (seed, location) in [(3, 103), (7, 107), (12, 207)] {
+            assert_eq!(part1(&[seed], &maps), location);
+            assert_eq!(composed.get(seed), location);
+        }
+    }
+
+    #[test]
+    fn composed_map_matches_the_example() {
+        let (seeds, maps) = parse(EXAMPLE);
+        let composed = PiecewiseMap::compose(&maps);
+
+        assert_eq!(part1(&seeds, &maps), 35);
+        assert_eq!(part1_composed(&seeds, &composed), 35);
+        assert_eq!(part2(&seeds, &maps), 46);
+        assert_eq!(part2_composed(&seeds, &composed), 46);
+    }
+
+    #[test]
+    fn tracing_back_finds_the_lowest_location_seeds() {
+        let (seeds, maps) = parse(EXAMPLE);
+
+        assert_eq!(
+            seeds_for_locations(&seeds, &maps, &Interval::with_len(35, 1)),
+            [13]
+        );
+        assert_eq!(
+            seed_ranges_for_locations(&seeds, &maps, &Interval::with_len(46, 1)),
+            [Interval::with_len(82, 1)]
+        );
+    }
+
+    #[test]
+    fn zero_length_seed_ranges_are_dropped() {
+        let (seeds, maps) = parse(EXAMPLE);
+
+        for start in [0, 50, 97, 98, 100] {
+            assert_eq!(handle_map(&Interval::with_len(start, 0), &maps[0]), []);
+        }
+
+        assert_eq!(part2(&[79, 0, 55, 13], &maps), part2(&seeds[2..], &maps));
+        assert_eq!(part2(&[79, 14, 55, 0], &maps), 46);
+    }
+
+    #[test]
+    fn split_at_clamps_to_the_interval() {
+        let interval = Interval::new(10, 20);
+
+        assert_eq!(
+            interval.split_at(5),
+            (Interval::new(10, 10), Interval::new(10, 20))
+        );
+        assert_eq!(
+            interval.split_at(15),
+            (Interval::new(10, 15), Interval::new(15, 20))
+        );
+        assert_eq!(
+            interval.split_at(25),
+            (Interval::new(10, 20), Interval::new(20, 20))
+        );
+    }
 }
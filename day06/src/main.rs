@@ -1,9 +1,9 @@
-use std::ops::Range;
+use std::{num::ParseIntError, ops::RangeInclusive};
 
 use nom::{
     bytes::complete::tag,
-    character::complete::{alpha0, char, newline, space1, i32},
-    multi::{separated_list0, separated_list1, many1},
+    character::complete::{newline, space1, u64},
+    multi::separated_list0,
     sequence::{preceded, tuple},
     IResult,
 };
@@ -13,26 +13,29 @@ fn read_input() -> String {
     input.to_string()
 }
 
-fn time(input: &str) -> IResult<&str, Vec<i32>> {
-    let (extra, times) = preceded(tuple((tag("Time:"), space1)), separated_list0(space1, i32))(input)?;
+fn time(input: &str) -> IResult<&str, Vec<u64>> {
+    let (extra, times) =
+        preceded(tuple((tag("Time:"), space1)), separated_list0(space1, u64))(input)?;
 
     Ok((extra, times))
 }
 
-fn records(input: &str) -> IResult<&str, Vec<i32>> {
-    let (extra, records) = preceded(tuple((tag("Distance:"), space1)), separated_list0(space1, i32))(input)?;
+fn records(input: &str) -> IResult<&str, Vec<u64>> {
+    let (extra, records) = preceded(
+        tuple((tag("Distance:"), space1)),
+        separated_list0(space1, u64),
+    )(input)?;
 
     Ok((extra, records))
 }
 
-fn whole_input(input: &str) -> IResult<&str, (Vec<i32>, Vec<i32>)> {
-    let (extra, (times, _, records)) =
-        tuple((time, newline, records))(input)?;
+fn whole_input(input: &str) -> IResult<&str, (Vec<u64>, Vec<u64>)> {
+    let (extra, (times, _, records)) = tuple((time, newline, records))(input)?;
 
     Ok((extra, (times, records)))
 }
 
-fn parse(input: &str) -> (Vec<i32>, Vec<i32>) {
+fn parse(input: &str) -> (Vec<u64>, Vec<u64>) {
     match whole_input(input) {
         Ok((_, result)) => result,
         Err(err) => {
@@ -42,49 +45,153 @@ fn parse(input: &str) -> (Vec<i32>, Vec<i32>) {
     }
 }
 
-fn get_winning_possibility_count(time: &i32, record: &i32) -> i32 {
-    (0..=*time).fold(0, |acc, hold_time| {
-        if i32::max(hold_time * ((*time) - hold_time), 0) > *record {
-            // println!("hold_time: {}", hold_time);
-            acc + 1
-        } else {
-            acc
-        }
-    })
+fn distance(time: u64, hold_time: u64) -> u128 {
+    hold_time as u128 * (time - hold_time) as u128
 }
 
-fn part1(times: &[i32], records: &[i32]) -> i32 {
-    times.iter().zip(records)
-        .map(|(time, record)| {
-            get_winning_possibility_count(time, record)
-        })
-        .product()
+/// Winning hold times are the integers strictly between the roots of `h * (time - h) = record`,
+/// which lie at `(time ± sqrt(time² - 4 * record)) / 2`.
+fn winning_hold_times(time: &u64, record: &u64) -> Option<RangeInclusive<u64>> {
+    // both sides fit in a u128 for every u64 time and record
+    let squared = (*time as u128).pow(2);
+    let quadrupled = 4 * *record as u128;
+
+    if squared <= quadrupled {
+        return None;
+    }
+
+    let discriminant = squared - quadrupled;
+
+    // the integer square root can put the estimate one step on either side of the real root
+    let mut shortest = ((*time as u128 - discriminant.isqrt()) / 2) as u64;
+
+    while shortest <= *time && distance(*time, shortest) <= *record as u128 {
+        shortest += 1;
+    }
+
+    while shortest > 0 && distance(*time, shortest - 1) > *record as u128 {
+        shortest -= 1;
+    }
+
+    // distances are symmetric around time / 2
+    let longest = *time - shortest.min(*time);
+
+    (shortest <= longest).then_some(shortest..=longest)
 }
 
-fn to_single_number(parts: &[i32]) -> i32 {
-    parts.iter().rev()
-        .fold((0, 1), |(total, index), part| {
-            let new_total = total + index * part;
-            let new_index = index * (10i32.pow(part.to_string().len() as u32));
+fn count_hold_times(hold_times: &Option<RangeInclusive<u64>>) -> u64 {
+    hold_times
+        .as_ref()
+        .map(|hold_times| hold_times.end() - hold_times.start() + 1)
+        .unwrap_or(0)
+}
 
-            (new_total, new_index)
-        }).0
+fn get_winning_possibility_count(time: &u64, record: &u64) -> u64 {
+    count_hold_times(&winning_hold_times(time, record))
 }
 
-fn part2(times: &[i32], records: &[i32]) -> i32 {
-    let time = to_single_number(times);
-    let record = to_single_number(records);
+#[derive(Debug)]
+struct RaceReport {
+    time: u64,
+    record: u64,
+    winning: Option<RangeInclusive<u64>>,
+    count: u64,
+    optimal_hold_time: u64,
+    optimal_distance: u128,
+}
 
-    let a: f64 = 1.0;
-    let b: f64 = (-time).into();
-    let c: f64 = record.into();
+impl RaceReport {
+    fn new(time: u64, record: u64) -> Self {
+        // for odd times time / 2 and time / 2 + 1 go equally far
+        let optimal_hold_time = time / 2;
+        let winning = winning_hold_times(&time, &record);
 
-    let d = b.powi(2) - 4.0 * a * c;
+        RaceReport {
+            time,
+            record,
+            count: count_hold_times(&winning),
+            winning,
+            optimal_hold_time,
+            optimal_distance: distance(time, optimal_hold_time),
+        }
+    }
 
-    let x1 = (-b + d.sqrt()) / (2.0 * a);
-    let x2 = (-b - d.sqrt()) / (2.0 * a);
+    /// How far the optimal strategy beats the record by, negative if it does not.
+    fn margin(&self) -> i128 {
+        self.optimal_distance as i128 - self.record as i128
+    }
 
-    (x1.ceil() - x2.ceil().abs()) as i32
+    /// How much the record would have to rise before no strategy wins any more.
+    fn record_headroom(&self) -> u128 {
+        self.optimal_distance.saturating_sub(self.record as u128)
+    }
+}
+
+fn print_reports(times: &[u64], records: &[u64], rise: Option<u64>) {
+    let mut races: Vec<(u64, u64)> = times
+        .iter()
+        .zip(records)
+        .map(|(time, record)| (*time, *record))
+        .collect();
+
+    match (to_single_number(times), to_single_number(records)) {
+        (Ok(time), Ok(record)) => races.push((time, record)),
+        (Err(err), _) | (_, Err(err)) => println!("joined race: {err}"),
+    }
+
+    for (time, record) in races {
+        let report = RaceReport::new(time, record);
+
+        println!("race of {}ms, record {}mm", report.time, report.record);
+
+        match &report.winning {
+            Some(hold_times) => println!(
+                "  winning hold times: {}..={} ({} ways)",
+                hold_times.start(),
+                hold_times.end(),
+                report.count
+            ),
+            None => println!("  no winning hold times"),
+        }
+
+        println!(
+            "  optimal hold time: {}ms for {}mm, margin {}mm",
+            report.optimal_hold_time,
+            report.optimal_distance,
+            report.margin()
+        );
+
+        if let Some(rise) = rise {
+            let raised = record.saturating_add(rise);
+
+            println!(
+                "  record could rise {}mm before no strategy wins, raised by {rise}mm there are {} ways",
+                report.record_headroom(),
+                get_winning_possibility_count(&time, &raised)
+            );
+        }
+    }
+}
+
+fn part1(times: &[u64], records: &[u64]) -> u64 {
+    times
+        .iter()
+        .zip(records)
+        .map(|(time, record)| get_winning_possibility_count(time, record))
+        .product()
+}
+
+/// The parts with their digits written one after another, or an error if that does not fit in
+/// a u64.
+fn to_single_number(parts: &[u64]) -> Result<u64, ParseIntError> {
+    parts.iter().map(u64::to_string).collect::<String>().parse()
+}
+
+fn part2(times: &[u64], records: &[u64]) -> Result<u64, ParseIntError> {
+    let time = to_single_number(times)?;
+    let record = to_single_number(records)?;
+
+    Ok(get_winning_possibility_count(&time, &record))
 }
 
 fn main() {
@@ -92,5 +199,68 @@ fn main() {
     let (times, records) = parse(&lines);
 
     println!("part1: {}", part1(&times, &records));
-    println!("part2: {}", part2(&times, &records));
+    match part2(&times, &records) {
+        Ok(count) => println!("part2: {count}"),
+        Err(err) => println!("part2: {err}"),
+    }
+
+    let args: Vec<String> = std::env::args().collect();
+
+    if let Some(index) = args.iter().position(|arg| arg == "--what-if") {
+        match args.get(index + 1).map(|rise| rise.parse::<u64>()) {
+            Some(Ok(rise)) => print_reports(&times, &records, Some(rise)),
+            _ => println!("usage: --what-if <rise>"),
+        }
+    } else if args.iter().any(|arg| arg == "--explore") {
+        print_reports(&times, &records, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force_winning_possibility_count(time: &u64, record: &u64) -> u64 {
+        (0..=*time).fold(0, |acc, hold_time| {
+            if distance(*time, hold_time) > *record as u128 {
+                acc + 1
+            } else {
+                acc
+            }
+        })
+    }
+
+    #[test]
+    fn closed_form_matches_brute_force() {
+        for time in 0..=200 {
+            for record in 0..=(time * time / 4 + 1) {
+                assert_eq!(
+                    get_winning_possibility_count(&time, &record),
+                    brute_force_winning_possibility_count(&time, &record),
+                    "time {time}, record {record}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn joined_numbers_up_to_the_largest_u64() {
+        assert_eq!(to_single_number(&[7, 15, 30]), Ok(71530));
+        assert_eq!(to_single_number(&[1844674407, 3709551615]), Ok(u64::MAX));
+        assert!(to_single_number(&[1844674407, 3709551616]).is_err());
+        assert!(to_single_number(&[u64::MAX, 0]).is_err());
+    }
+
+    #[test]
+    fn closed_form_is_exact_for_the_largest_times() {
+        assert_eq!(get_winning_possibility_count(&u64::MAX, &0), u64::MAX - 1);
+        assert_eq!(
+            get_winning_possibility_count(&u64::MAX, &u64::MAX),
+            u64::MAX - 3
+        );
+        assert_eq!(
+            get_winning_possibility_count(&(u64::MAX - 1), &0),
+            u64::MAX - 2
+        );
+    }
 }
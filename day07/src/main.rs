@@ -1,4 +1,4 @@
-use std::{cmp::Ordering, fmt, str::FromStr};
+use std::{cmp::Ordering, fmt, str::FromStr, sync::LazyLock};
 
 fn read_input() -> Vec<String> {
     let input = include_str!("../input");
@@ -8,14 +8,13 @@ fn read_input() -> Vec<String> {
         .collect()
 }
 
-#[repr(u8)]
 #[derive(Clone)]
 enum Card {
     Number(u8),
-    JackJoker = 11,
-    Queen = 12,
-    King = 13,
-    Ace = 14,
+    JackJoker,
+    Queen,
+    King,
+    Ace,
 }
 
 impl fmt::Debug for Card {
@@ -32,14 +31,34 @@ impl fmt::Debug for Card {
 }
 
 impl Card {
-    fn value(&self) -> u8 {
+    /// The card's face value, from 2 up to 14 for an ace.
+    const fn value(&self) -> u8 {
         match self {
             Card::Number(n) => *n,
-            _ => unsafe { *(self as *const Self).cast::<u8>() },
+            Card::JackJoker => 11,
+            Card::Queen => 12,
+            Card::King => 13,
+            Card::Ace => 14,
         }
     }
 }
 
+const CARD_ORDER: [Card; CARD_OPTIONS] = [
+    Card::Number(2),
+    Card::Number(3),
+    Card::Number(4),
+    Card::Number(5),
+    Card::Number(6),
+    Card::Number(7),
+    Card::Number(8),
+    Card::Number(9),
+    Card::Number(10),
+    Card::JackJoker,
+    Card::Queen,
+    Card::King,
+    Card::Ace,
+];
+
 impl FromStr for Card {
     type Err = String;
 
@@ -79,8 +98,7 @@ impl Eq for Card {}
 
 const CARD_OPTIONS: usize = 13;
 
-#[repr(u8)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum HandType {
     HighCard,
     OnePair,
@@ -93,8 +111,10 @@ enum HandType {
 
 #[derive(Clone)]
 struct Hand {
-    cards: [Card; 5],
+    cards: Vec<Card>,
     bid: u32,
+    /// Line number in the input, which breaks ties between hands with identical cards.
+    position: usize,
 }
 
 impl fmt::Debug for Hand {
@@ -105,162 +125,254 @@ impl fmt::Debug for Hand {
 }
 
 impl Hand {
-    fn get_type(&self) -> HandType {
+    /// How often each distinct card occurs, most common first.
+    fn card_counts(&self) -> Vec<usize> {
         let mut occurrences = [0; CARD_OPTIONS];
 
-        let max_occurences = self.cards.iter().fold(0, |acc, card| {
-            let value = card.value();
-            let card_index = (value - 2) as usize;
-            occurrences[card_index] += 1;
+        for card in &self.cards {
+            occurrences[(card.value() - 2) as usize] += 1;
+        }
 
-            let count = occurrences[card_index];
+        let mut counts: Vec<usize> = occurrences.into_iter().filter(|&count| count > 0).collect();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        counts
+    }
 
-            acc.max(count)
-        });
+    fn get_type(&self) -> HandType {
+        let counts = self.card_counts();
 
-        match max_occurences {
-            5 => HandType::FiveOfAKind,
-            4 => HandType::FourOfAKind,
-            1 => HandType::HighCard,
-            3 => {
-                if occurrences.contains(&2) {
-                    HandType::FullHouse
-                } else {
-                    HandType::ThreeOfAKind
-                }
+        HAND_TYPE_PATTERNS
+            .iter()
+            .find(|(pattern, _)| {
+                pattern.len() <= counts.len()
+                    && pattern
+                        .iter()
+                        .zip(&counts)
+                        .all(|(needed, count)| count >= needed)
+            })
+            .map(|(_, hand_type)| *hand_type)
+            .unwrap()
+    }
+
+    /// This hand with every wild card replaced by whichever card gives the best hand type:
+    /// the most common non-wild card, the strongest one on a tie.
+    fn substitute_wild(&self, rules: &Rules) -> Hand {
+        let mut counts: Vec<(usize, &Card)> = vec![];
+
+        for card in self.cards.iter().filter(|card| !rules.is_wild(card)) {
+            match counts.iter_mut().find(|(_, counted)| *counted == card) {
+                Some((count, _)) => *count += 1,
+                None => counts.push((1, card)),
             }
-            2 => {
-                if occurrences
-                    .iter()
-                    .filter(|&occurrences| *occurrences == 2)
-                    .count()
-                    == 2
-                {
-                    HandType::TwoPair
+        }
+
+        let replacement = counts
+            .iter()
+            .max_by(|(count_a, card_a), (count_b, card_b)| {
+                count_a
+                    .cmp(count_b)
+                    .then_with(|| rules.strength(card_a).cmp(&rules.strength(card_b)))
+            })
+            .map(|(_, card)| (*card).clone())
+            .unwrap_or_else(|| rules.strongest());
+
+        let cards = self
+            .cards
+            .iter()
+            .map(|card| {
+                if rules.is_wild(card) {
+                    replacement.clone()
                 } else {
-                    HandType::OnePair
+                    card.clone()
                 }
-            }
-            _ => unreachable!("Max occurrences should be in [1; 5]"),
+            })
+            .collect();
+
+        Hand {
+            cards,
+            bid: self.bid,
+            position: self.position,
         }
     }
+}
 
-    fn has_joker(&self) -> bool {
-        self.cards.contains(&Card::JackJoker)
-    }
-
-    fn get_joker_type(&self) -> HandType {
-        if self.has_joker() {
-            let mut occurrences = [0; CARD_OPTIONS];
-            let joker_position: usize = 9;
-
-            let max_occurences = self.cards.iter().fold(0, |acc, card| {
-                let value = card.value();
-                let card_index = (value - 2) as usize;
-                occurrences[card_index] += 1;
-
-                let count = occurrences[card_index];
-
-                acc.max(count)
-            });
-
-            match max_occurences {
-                4 | 5 => HandType::FiveOfAKind,
-                1 => HandType::OnePair,
-                3 => {
-                    if occurrences[joker_position] == 3 {
-                        if occurrences.contains(&2) {
-                            HandType::FiveOfAKind
-                        } else {
-                            HandType::FourOfAKind
-                        }
-                    } else if occurrences[joker_position] == 2 {
-                        HandType::FiveOfAKind
-                    } else if occurrences[joker_position] == 1 {
-                        HandType::FourOfAKind
-                    } else {
-                        unreachable!();
-                    }
-                }
-                2 => {
-                    if occurrences
-                        .iter()
-                        .filter(|&occurrences| *occurrences == 2)
-                        .count()
-                        == 2
-                    {
-                        if occurrences[joker_position] == 2 {
-                            HandType::FourOfAKind
-                        } else {
-                            HandType::FullHouse
-                        }
-                    } else {
-                        HandType::ThreeOfAKind
-                    }
-                }
-                _ => unreachable!("Max occurrences should be in [1; 5]"),
-            }
-        } else {
-            self.get_type()
+/// Everything that differs between variants of Camel Cards.
+#[derive(Debug)]
+struct Rules {
+    /// Cards that stand in for whichever card makes the best hand.
+    wild: Vec<Card>,
+    /// All cards, weakest first.
+    strength_order: Vec<Card>,
+    /// The hand types that exist, weakest first. A hand whose type does not exist counts as the
+    /// strongest type below it that does.
+    hand_types: Vec<HandType>,
+}
+
+impl Rules {
+    fn standard() -> Self {
+        Rules {
+            wild: vec![],
+            strength_order: CARD_ORDER.to_vec(),
+            hand_types: HAND_TYPE_ORDER.to_vec(),
         }
     }
-}
 
-fn cmp_with_jokers(first: &Hand, second: &Hand) -> std::cmp::Ordering {
-    let first_highest_possible = first.get_joker_type();
-    let second_highest_possible = second.get_joker_type();
-
-    if first_highest_possible.value() == second_highest_possible.value() {
-        for (card_first, card_second) in first.cards.iter().zip(&second.cards) {
-            let first_value = match card_first.value() {
-                11 => 1,
-                d => d,
-            };
-
-            let second_value = match card_second.value() {
-                11 => 1,
-                d => d,
-            };
-
-            match first_value.cmp(&second_value) {
-                Ordering::Equal => (),
-                other => return other
-            }
+    /// Jacks become jokers: wild, and weaker than any other card.
+    fn jokers() -> Self {
+        Rules::standard().with_wild(Card::JackJoker)
+    }
+
+    fn aces_low() -> Self {
+        let mut rules = Rules::standard();
+        rules.strength_order.rotate_right(1);
+        rules
+    }
+
+    /// Make `card` wild, and weaker than any other card.
+    fn with_wild(mut self, card: Card) -> Self {
+        self.strength_order.retain(|other| *other != card);
+        self.strength_order.insert(0, card.clone());
+        self.wild.push(card);
+        self
+    }
+
+    fn by_name(name: &str) -> Option<Self> {
+        let rules = match name {
+            "standard" => Rules::standard(),
+            "jokers" => Rules::jokers(),
+            "jokers-and-deuces" => Rules::jokers().with_wild(Card::Number(2)),
+            "aces-low" => Rules::aces_low(),
+            _ => return None,
+        };
+
+        Some(rules)
+    }
+
+    fn is_wild(&self, card: &Card) -> bool {
+        self.wild.contains(card)
+    }
+
+    fn strength(&self, card: &Card) -> usize {
+        self.strength_order
+            .iter()
+            .position(|other| other == card)
+            .unwrap()
+    }
+
+    fn strongest(&self) -> Card {
+        self.strength_order.last().unwrap().clone()
+    }
+
+    fn hand_type(&self, hand: &Hand) -> HandType {
+        let natural = hand.substitute_wild(self).get_type();
+
+        self.hand_types
+            .iter()
+            .filter(|hand_type| hand_type.value() <= natural.value())
+            .max_by_key(|hand_type| hand_type.value())
+            .or(self.hand_types.first())
+            .copied()
+            .unwrap()
+    }
+
+    fn hand_type_strength(&self, hand: &Hand) -> usize {
+        let hand_type = self.hand_type(hand);
+
+        self.hand_types
+            .iter()
+            .position(|other| *other == hand_type)
+            .unwrap()
+    }
+
+    /// What decides the order of two hands: their types, otherwise the first card that differs,
+    /// otherwise their position in the input.
+    fn decider(&self, first: &Hand, second: &Hand) -> Decider {
+        if self.hand_type_strength(first) != self.hand_type_strength(second) {
+            return Decider::HandType;
         }
 
-        unreachable!()
-    } else {
-        first_highest_possible
-            .value()
-            .cmp(&second_highest_possible.value())
+        first
+            .cards
+            .iter()
+            .zip(&second.cards)
+            .position(|(card_first, card_second)| {
+                self.strength(card_first) != self.strength(card_second)
+            })
+            .map_or(Decider::Position, Decider::Card)
+    }
+
+    /// Everything hands are ordered by: type, then card by card, then position in the input for
+    /// hands with identical cards, and then bid.
+    fn sort_key(&self, hand: &Hand) -> (usize, Vec<usize>, usize, u32) {
+        let strengths = hand.cards.iter().map(|card| self.strength(card)).collect();
+
+        (
+            self.hand_type_strength(hand),
+            strengths,
+            hand.position,
+            hand.bid,
+        )
+    }
+
+    fn cmp(&self, first: &Hand, second: &Hand) -> Ordering {
+        self.sort_key(first).cmp(&self.sort_key(second))
+    }
+
+    /// Sort hands weakest first, working out each hand's type only once.
+    fn sort(&self, hands: &mut [Hand]) {
+        hands.sort_by_cached_key(|hand| self.sort_key(hand));
     }
 }
 
-fn order_with_jokers(hands: &mut [Hand]) {
-    hands.sort_by(cmp_with_jokers);
+static STANDARD_RULES: LazyLock<Rules> = LazyLock::new(Rules::standard);
+
+#[derive(Debug)]
+enum Decider {
+    HandType,
+    Card(usize),
+    Position,
 }
 
 impl HandType {
-    fn value(&self) -> u8 {
-        unsafe { *(self as *const Self).cast::<u8>() }
+    /// The hand type's strength, from 0 for a high card up to 6 for five of a kind.
+    const fn value(&self) -> u8 {
+        match self {
+            HandType::HighCard => 0,
+            HandType::OnePair => 1,
+            HandType::TwoPair => 2,
+            HandType::ThreeOfAKind => 3,
+            HandType::FullHouse => 4,
+            HandType::FourOfAKind => 5,
+            HandType::FiveOfAKind => 6,
+        }
     }
 }
 
+/// The card counts each hand type needs, strongest type first. A hand has the first type whose
+/// pattern its own most common counts cover, so hands of any size can be classified.
+const HAND_TYPE_PATTERNS: [(&[usize], HandType); 7] = [
+    (&[5], HandType::FiveOfAKind),
+    (&[4], HandType::FourOfAKind),
+    (&[3, 2], HandType::FullHouse),
+    (&[3], HandType::ThreeOfAKind),
+    (&[2, 2], HandType::TwoPair),
+    (&[2], HandType::OnePair),
+    (&[], HandType::HighCard),
+];
+
+const HAND_TYPE_ORDER: [HandType; 7] = [
+    HandType::HighCard,
+    HandType::OnePair,
+    HandType::TwoPair,
+    HandType::ThreeOfAKind,
+    HandType::FullHouse,
+    HandType::FourOfAKind,
+    HandType::FiveOfAKind,
+];
+
 impl Ord for Hand {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match self.get_type().value().cmp(&other.get_type().value()) {
-            Ordering::Equal => {
-                for (card_self, card_other) in self.cards.iter().zip(&other.cards) {
-                    match card_self.value().cmp(&card_other.value()) {
-                        Ordering::Equal => (),
-                        other => return other
-                    }
-                }
-
-                unreachable!("Hand types are equal, at least one card must be higher");
-            }
-            other => other,
-        }
+        STANDARD_RULES.cmp(self, other)
     }
 }
 
@@ -271,8 +383,8 @@ impl PartialOrd for Hand {
 }
 
 impl PartialEq for Hand {
-    fn eq(&self, _: &Self) -> bool {
-        false
+    fn eq(&self, other: &Self) -> bool {
+        self.cards == other.cards && self.position == other.position && self.bid == other.bid
     }
 }
 
@@ -281,24 +393,27 @@ impl Eq for Hand {}
 fn parse(lines: &[String]) -> Vec<Hand> {
     lines
         .iter()
-        .map(|line| {
+        .enumerate()
+        .map(|(position, line)| {
             let (cards_str, bid) = line.split_once(' ').unwrap();
 
-            let cards: [Card; 5] = cards_str
+            let cards = cards_str
                 .chars()
                 .map(|c| Card::from_str(&c.to_string()).unwrap())
-                .collect::<Vec<Card>>()
-                .try_into()
-                .unwrap();
+                .collect();
             let bid = bid.parse().unwrap();
 
-            Hand { cards, bid }
+            Hand {
+                cards,
+                bid,
+                position,
+            }
         })
         .collect()
 }
 
-fn part1(hands: &mut [Hand]) -> u32 {
-    hands.sort();
+fn total_winnings(hands: &mut [Hand], rules: &Rules) -> u32 {
+    rules.sort(hands);
 
     hands.iter().enumerate().fold(0, |acc, (rank, hand)| {
         let rank: u32 = (rank + 1).try_into().unwrap();
@@ -307,14 +422,72 @@ fn part1(hands: &mut [Hand]) -> u32 {
     })
 }
 
-fn part2(hands: &mut [Hand]) -> u32 {
-    order_with_jokers(hands);
+fn cards_to_string(cards: &[Card]) -> String {
+    cards.iter().map(|card| format!("{card:?}")).collect()
+}
 
-    hands.iter().enumerate().fold(0, |acc, (rank, hand)| {
-        let rank: u32 = (rank + 1).try_into().unwrap();
-        let hand_value = rank * hand.bid;
-        acc + hand_value
-    })
+fn describe_decider(rules: &Rules, hand: &Hand, neighbour: &Hand) -> String {
+    match rules.decider(hand, neighbour) {
+        Decider::HandType => format!(
+            "type: {:?} vs {:?}",
+            rules.hand_type(hand),
+            rules.hand_type(neighbour)
+        ),
+        Decider::Card(index) => format!(
+            "card {}: {:?} vs {:?}",
+            index + 1,
+            hand.cards[index],
+            neighbour.cards[index]
+        ),
+        Decider::Position => format!("input line {}", neighbour.position + 1),
+    }
+}
+
+/// Print every hand in rank order, with why it sits between its neighbours.
+fn explain(hands: &mut [Hand], rules: &Rules) {
+    rules.sort(hands);
+
+    for (index, hand) in hands.iter().enumerate() {
+        let rank = index as u32 + 1;
+        let substituted = hand.substitute_wild(rules);
+
+        println!(
+            "rank {rank}: {} bid {} wins {}",
+            cards_to_string(&hand.cards),
+            hand.bid,
+            rank * hand.bid
+        );
+        println!(
+            "  type {:?}, best as {} {:?}",
+            hand.get_type(),
+            cards_to_string(&substituted.cards),
+            rules.hand_type(hand)
+        );
+
+        if let Some(below) = index.checked_sub(1).map(|below| &hands[below]) {
+            println!(
+                "  above {} by {}",
+                cards_to_string(&below.cards),
+                describe_decider(rules, hand, below)
+            );
+        }
+
+        if let Some(above) = hands.get(index + 1) {
+            println!(
+                "  below {} by {}",
+                cards_to_string(&above.cards),
+                describe_decider(rules, hand, above)
+            );
+        }
+    }
+}
+
+fn part1(hands: &mut [Hand]) -> u32 {
+    total_winnings(hands, &Rules::standard())
+}
+
+fn part2(hands: &mut [Hand]) -> u32 {
+    total_winnings(hands, &Rules::jokers())
 }
 
 fn main() {
@@ -323,4 +496,78 @@ fn main() {
 
     println!("part1: {}", part1(&mut hands));
     println!("part2: {}", part2(&mut hands));
+
+    let args: Vec<String> = std::env::args().collect();
+
+    if let Some(index) = args.iter().position(|arg| arg == "--explain") {
+        let names = match args.get(index + 1) {
+            Some(name) => vec![name.as_str()],
+            None => vec!["standard", "jokers"],
+        };
+
+        for name in names {
+            match Rules::by_name(name) {
+                Some(rules) => {
+                    println!("{name}:");
+                    explain(&mut hands, &rules);
+                }
+                None => println!("unknown rules '{name}'"),
+            }
+        }
+    }
+
+    if let Some(index) = args.iter().position(|arg| arg == "--rules") {
+        let name = args.get(index + 1).map(String::as_str).unwrap_or_default();
+
+        match Rules::by_name(name) {
+            Some(rules) => println!("{name}: {}", total_winnings(&mut hands, &rules)),
+            None => println!("unknown rules '{name}'"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(cards: &str) -> Hand {
+        parse(&[format!("{cards} 1")]).remove(0)
+    }
+
+    #[test]
+    fn cards_are_ordered_weakest_first() {
+        let cards: Vec<Card> = "23456789TJQKA"
+            .chars()
+            .map(|c| Card::from_str(&c.to_string()).unwrap())
+            .collect();
+
+        assert_eq!(cards, CARD_ORDER);
+
+        let rules = Rules::standard();
+
+        for pair in cards.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} < {:?}", pair[0], pair[1]);
+            assert!(rules.strength(&pair[0]) < rules.strength(&pair[1]));
+        }
+    }
+
+    #[test]
+    fn hand_types_are_ordered_weakest_first() {
+        let hands: Vec<Hand> = [
+            "23456", "22345", "22335", "22234", "22233", "22223", "22222",
+        ]
+        .into_iter()
+        .map(hand)
+        .collect();
+
+        let types: Vec<HandType> = hands.iter().map(Hand::get_type).collect();
+        assert_eq!(types, HAND_TYPE_ORDER);
+
+        let rules = Rules::standard();
+
+        for pair in hands.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} < {:?}", pair[0], pair[1]);
+            assert!(rules.hand_type_strength(&pair[0]) < rules.hand_type_strength(&pair[1]));
+        }
+    }
 }
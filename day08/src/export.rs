@@ -0,0 +1,238 @@
+use std::{collections::HashSet, fmt::Write};
+
+use crate::{walk, Direction, Directions, Network, NodeId, WalkEnd};
+
+/// The nodes visited walking from `start`, up to the point where the walk starts repeating itself,
+/// and the index in that list at which the repeating part begins.
+pub struct Walk {
+    nodes: Vec<NodeId>,
+    /// The direction taken out of each node but the last.
+    turns: Vec<Direction>,
+    loop_start: usize,
+}
+
+impl Walk {
+    pub fn new(network: &Network, directions: &Directions, start: NodeId) -> Walk {
+        let mut nodes = vec![];
+
+        let end = walk(network, directions, start, |id, _| {
+            nodes.push(id);
+
+            true
+        });
+
+        match end {
+            WalkEnd::Repeated {
+                first_visit, node, ..
+            } => {
+                // close the loop so its last edge is part of the walk
+                nodes.push(node);
+
+                let turns = (0..nodes.len() - 1)
+                    .map(|step| directions[step % directions.len()])
+                    .collect();
+
+                Walk {
+                    nodes,
+                    turns,
+                    loop_start: first_visit,
+                }
+            }
+            WalkEnd::Stopped { .. } => unreachable!("The walk is never stopped early"),
+        }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (NodeId, Direction)> + '_ {
+        self.nodes.iter().copied().zip(self.turns.iter().copied())
+    }
+}
+
+/// Everything the exporters draw on top of the plain network.
+pub struct Annotations {
+    pub is_start: Vec<bool>,
+    pub is_end: Vec<bool>,
+    /// Nodes on the repeating part of the walk from any start.
+    pub on_cycle: Vec<bool>,
+    /// Weakly connected component of every node.
+    pub component: Vec<usize>,
+    pub highlight: Option<Walk>,
+    highlighted_edges: HashSet<(NodeId, Direction)>,
+}
+
+impl Annotations {
+    pub fn new(
+        network: &Network,
+        directions: &Directions,
+        is_start: Vec<bool>,
+        is_end: Vec<bool>,
+        highlight: Option<Walk>,
+    ) -> Annotations {
+        let mut on_cycle = vec![false; network.len()];
+
+        for start in (0..network.len()).filter(|&id| is_start[id]) {
+            let walk = Walk::new(network, directions, start);
+
+            for &id in &walk.nodes[walk.loop_start..] {
+                on_cycle[id] = true;
+            }
+        }
+
+        let highlighted_edges = highlight
+            .as_ref()
+            .map(|walk| walk.edges().collect())
+            .unwrap_or_default();
+
+        Annotations {
+            is_start,
+            is_end,
+            on_cycle,
+            component: components(network),
+            highlight,
+            highlighted_edges,
+        }
+    }
+}
+
+fn root(parent: &mut [NodeId], id: NodeId) -> NodeId {
+    let mut id = id;
+    while parent[id] != id {
+        parent[id] = parent[parent[id]];
+        id = parent[id];
+    }
+    id
+}
+
+fn components(network: &Network) -> Vec<usize> {
+    let mut parent: Vec<NodeId> = (0..network.len()).collect();
+
+    for id in 0..network.len() {
+        for neighbour in network.links[id] {
+            let (a, b) = (root(&mut parent, id), root(&mut parent, neighbour));
+            parent[a] = b;
+        }
+    }
+
+    // number the components in order of their first node
+    let mut numbers: Vec<Option<usize>> = vec![None; network.len()];
+    let mut count = 0;
+
+    (0..network.len())
+        .map(|id| {
+            let root = root(&mut parent, id);
+
+            *numbers[root].get_or_insert_with(|| {
+                count += 1;
+                count - 1
+            })
+        })
+        .collect()
+}
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub fn to_dot(network: &Network, annotations: &Annotations) -> String {
+    let mut dot = String::new();
+
+    writeln!(dot, "digraph network {{").unwrap();
+
+    let component_count = annotations.component.iter().max().map_or(0, |max| max + 1);
+
+    for component in 0..component_count {
+        writeln!(dot, "    subgraph cluster_{component} {{").unwrap();
+        writeln!(dot, "        label = \"component {component}\";").unwrap();
+
+        for id in (0..network.len()).filter(|&id| annotations.component[id] == component) {
+            let mut attributes = vec![];
+
+            if annotations.is_start[id] {
+                attributes.push("shape = box");
+            }
+
+            if annotations.is_end[id] {
+                attributes.push("style = filled");
+                attributes.push(if annotations.on_cycle[id] {
+                    "fillcolor = gold"
+                } else {
+                    "fillcolor = lightgrey"
+                });
+            }
+
+            if annotations.on_cycle[id] {
+                attributes.push("penwidth = 2");
+            }
+
+            writeln!(
+                dot,
+                "        \"{}\" [{}];",
+                escape(network.name(id)),
+                attributes.join(", ")
+            )
+            .unwrap();
+        }
+
+        writeln!(dot, "    }}").unwrap();
+    }
+
+    for id in 0..network.len() {
+        for (direction, label, style) in [
+            (Direction::Left, "L", "solid"),
+            (Direction::Right, "R", "dashed"),
+        ] {
+            let neighbour = network.step(id, &direction);
+            let colour = if annotations.highlighted_edges.contains(&(id, direction)) {
+                "red"
+            } else {
+                "black"
+            };
+
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label = \"{label}\", style = {style}, color = {colour}];",
+                escape(network.name(id)),
+                escape(network.name(neighbour))
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(dot, "}}").unwrap();
+
+    dot
+}
+
+pub fn to_json(network: &Network, annotations: &Annotations) -> String {
+    let nodes: Vec<String> = (0..network.len())
+        .map(|id| {
+            format!(
+                "    {{\"name\": \"{}\", \"left\": \"{}\", \"right\": \"{}\", \"component\": {}, \"start\": {}, \"end\": {}, \"on_cycle\": {}}}",
+                escape(network.name(id)),
+                escape(network.name(network.step(id, &Direction::Left))),
+                escape(network.name(network.step(id, &Direction::Right))),
+                annotations.component[id],
+                annotations.is_start[id],
+                annotations.is_end[id],
+                annotations.on_cycle[id]
+            )
+        })
+        .collect();
+
+    let path = match &annotations.highlight {
+        Some(walk) => {
+            let names: Vec<String> = walk
+                .nodes
+                .iter()
+                .map(|&id| format!("\"{}\"", escape(network.name(id))))
+                .collect();
+
+            format!("[{}]", names.join(", "))
+        }
+        None => "null".to_string(),
+    };
+
+    format!(
+        "{{\n  \"nodes\": [\n{}\n  ],\n  \"path\": {path}\n}}\n",
+        nodes.join(",\n")
+    )
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
 use nom::{
     bytes::complete::tag,
@@ -8,11 +8,14 @@ use nom::{
     IResult, branch::alt,
 };
 
+mod export;
+
 fn read_input() -> String {
     let input = include_str!("../input");
     input.to_string()
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 enum Direction {
     Left,
     Right,
@@ -29,6 +32,95 @@ struct Node {
 
 type Nodes = Vec<Node>;
 
+type NodeId = usize;
+
+#[derive(Debug)]
+enum WalkError {
+    MissingNode { node: String, neighbour: String },
+    UnknownStart(String),
+    Unreachable { start: String },
+    NoGhosts,
+    NoCommonStep,
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WalkError::MissingNode { node, neighbour } => {
+                write!(f, "{node} leads to {neighbour}, which doesn't exist")
+            }
+            WalkError::UnknownStart(start) => write!(f, "there is no node {start}"),
+            WalkError::Unreachable { start } => {
+                write!(f, "no target can be reached from {start}")
+            }
+            WalkError::NoGhosts => write!(f, "no node matches the ghost start"),
+            WalkError::NoCommonStep => {
+                write!(f, "the ghosts never reach end nodes at the same time")
+            }
+        }
+    }
+}
+
+/// The nodes compiled into an adjacency array. Node names are interned once, after which walking
+/// the network only indexes into `links`.
+struct Network {
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    links: Vec<[NodeId; 2]>,
+}
+
+impl Network {
+    fn compile(nodes: &Nodes) -> Result<Network, WalkError> {
+        let names: Vec<String> = nodes.iter().map(|node| node.id.clone()).collect();
+
+        let ids: HashMap<String, NodeId> = names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.clone(), id))
+            .collect();
+
+        let lookup = |node: &Node, neighbour: &String| {
+            ids.get(neighbour)
+                .copied()
+                .ok_or_else(|| WalkError::MissingNode {
+                    node: node.id.clone(),
+                    neighbour: neighbour.clone(),
+                })
+        };
+
+        let links = nodes
+            .iter()
+            .map(|node| Ok([lookup(node, &node.left)?, lookup(node, &node.right)?]))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Network { names, ids, links })
+    }
+
+    /// For every node, whether its name satisfies `predicate`.
+    fn select(&self, predicate: impl Fn(&str) -> bool) -> Vec<bool> {
+        self.names.iter().map(|name| predicate(name)).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    fn name(&self, id: NodeId) -> &str {
+        &self.names[id]
+    }
+
+    fn step(&self, id: NodeId, direction: &Direction) -> NodeId {
+        match direction {
+            Direction::Left => self.links[id][0],
+            Direction::Right => self.links[id][1],
+        }
+    }
+}
+
 fn directions(input: &str) -> IResult<&str, Directions> {
     let (extra, directions) =
         many1(
@@ -92,60 +184,146 @@ fn parse(input: &str) -> (Directions, Nodes) {
     }
 }
 
-fn part1(directions: &Directions, nodes: &Nodes) -> usize {
-    let node_map: HashMap<String, Node> = nodes.iter()
-        .map(|node| {
-            (node.id.clone(), node.clone())
-        })
-        .collect();
+/// How a walk ended: either its visitor stopped it, or it came back to a state it had already
+/// been in.
+enum WalkEnd {
+    Stopped {
+        steps: usize,
+    },
+    Repeated {
+        steps: usize,
+        first_visit: usize,
+        node: NodeId,
+        instruction: usize,
+    },
+}
 
+/// Follow `directions` from `start`, showing `visit` every node along with the step it is reached
+/// on. The walk's state is the current node together with the index of the next instruction, so
+/// it ends when a state repeats, or earlier if `visit` returns `false`.
+fn walk(
+    network: &Network,
+    directions: &Directions,
+    start: NodeId,
+    mut visit: impl FnMut(NodeId, usize) -> bool,
+) -> WalkEnd {
     let dir_len = directions.len();
+    let mut first_visit: Vec<Option<usize>> = vec![None; network.len() * dir_len];
 
-    let mut current_id = "AAA";
+    let mut current_id = start;
     let mut steps = 0;
-    while current_id != "ZZZ" {
-        let current_direction = &directions[steps % dir_len];
-        let current_node = node_map.get(current_id).unwrap_or_else(|| panic!("Every node should exist, {current_id} doesn't"));
-
-        current_id =
-            match current_direction {
-                Direction::Left => {
-                    &current_node.left
-                },
-                Direction::Right => {
-                    &current_node.right
-                },
+    loop {
+        let instruction = steps % dir_len;
+        let state = current_id * dir_len + instruction;
+
+        if let Some(first_visit) = first_visit[state] {
+            return WalkEnd::Repeated {
+                steps,
+                first_visit,
+                node: current_id,
+                instruction,
             };
+        }
+
+        if !visit(current_id, steps) {
+            return WalkEnd::Stopped { steps };
+        }
+
+        first_visit[state] = Some(steps);
+        current_id = network.step(current_id, &directions[instruction]);
 
         steps += 1;
     }
+}
 
-    steps
+/// Steps from `start` until the first node for which `is_target` is set. Gives up as soon as the
+/// walk is back at a node it visited before at the same point in the instructions.
+fn count_steps(
+    network: &Network,
+    directions: &Directions,
+    start: NodeId,
+    is_target: &[bool],
+) -> Result<usize, WalkError> {
+    match walk(network, directions, start, |id, _| !is_target[id]) {
+        WalkEnd::Stopped { steps } => Ok(steps),
+        WalkEnd::Repeated { .. } => Err(WalkError::Unreachable {
+            start: network.name(start).to_string(),
+        }),
+    }
 }
 
-fn count_steps(node_map: &HashMap<String, Node>, directions: &Directions, start: &str) -> usize {
-    let dir_len = directions.len();
+fn part1(
+    directions: &Directions,
+    network: &Network,
+    start: &str,
+    is_target: impl Fn(&str) -> bool,
+) -> Result<usize, WalkError> {
+    let start_id = network
+        .id(start)
+        .ok_or_else(|| WalkError::UnknownStart(start.to_string()))?;
+
+    count_steps(network, directions, start_id, &network.select(is_target))
+}
 
-    let mut current_id = start;
-    let mut steps = 0;
-    while !current_id.ends_with('Z') {
-        let current_direction = &directions[steps % dir_len];
-        let current_node = node_map.get(current_id).expect("Every node should exist");
-
-        current_id =
-            match current_direction {
-                Direction::Left => {
-                    &current_node.left
-                },
-                Direction::Right => {
-                    &current_node.right
-                },
-            };
+/// How the walk from `start` settles into a loop. The walk's state is the current node together
+/// with the index of the next instruction, so it has to repeat eventually.
+#[derive(Debug)]
+struct GhostCycle {
+    start: NodeId,
+    /// The first state that is visited twice.
+    entry: (NodeId, usize),
+    /// Steps taken before reaching `entry` for the first time.
+    prefix: usize,
+    /// Steps from `entry` back to itself.
+    cycle: usize,
+    /// Every step before `prefix + cycle` on which the walk is at an end node. Hits from
+    /// `prefix` onwards repeat every `cycle` steps.
+    z_hits: Vec<usize>,
+}
 
-        steps += 1;
+impl GhostCycle {
+    fn analyse(
+        network: &Network,
+        directions: &Directions,
+        start: NodeId,
+        is_end: &[bool],
+    ) -> GhostCycle {
+        let mut z_hits = vec![];
+
+        let end = walk(network, directions, start, |id, steps| {
+            if is_end[id] {
+                z_hits.push(steps);
+            }
+
+            true
+        });
+
+        match end {
+            WalkEnd::Repeated {
+                steps,
+                first_visit: prefix,
+                node,
+                instruction,
+            } => GhostCycle {
+                start,
+                entry: (node, instruction),
+                prefix,
+                cycle: steps - prefix,
+                z_hits,
+            },
+            WalkEnd::Stopped { .. } => unreachable!("The walk is never stopped early"),
+        }
     }
 
-    steps
+    fn hits(&self, step: usize) -> bool {
+        let step = if step < self.prefix {
+            step
+        } else {
+            self.prefix + (step - self.prefix) % self.cycle
+        };
+
+        self.z_hits.binary_search(&step).is_ok()
+    }
 }
 
 fn gcd(a: usize, b: usize) -> usize {
@@ -161,50 +339,380 @@ fn gcd(a: usize, b: usize) -> usize {
     a
 }
 
-fn lcm_two(a: usize, b: usize) -> usize {
-    (a * b).div_euclid(gcd(a, b))
+/// Returns `(gcd, x, y)` with `a * x + b * y == gcd`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
 }
 
-fn lcm(numbers: &[usize]) -> usize {
-    let a: Vec<usize> = numbers.to_vec();
-    a.into_iter().reduce(|acc, e| {
-        lcm_two(acc, e)
-    }).unwrap()
+/// All steps `t >= from` with `t % modulus == residue`.
+#[derive(Debug, Clone, Copy)]
+struct Progression {
+    residue: usize,
+    modulus: usize,
+    from: usize,
 }
 
-fn part2(directions: &Directions, nodes: &Nodes) -> usize {
-    let node_map: HashMap<String, Node> = nodes.iter()
-        .map(|node| {
-            (node.id.clone(), node.clone())
-        })
-        .collect();
+impl Progression {
+    /// The steps in both progressions, by the generalised Chinese remainder theorem.
+    fn intersect(&self, other: &Progression) -> Option<Progression> {
+        let g = gcd(self.modulus, other.modulus);
+        let difference = other.residue as i128 - self.residue as i128;
 
-    let start_points: Vec<String> = nodes.iter()
-        .filter_map(|node| {
-            if node.id.ends_with('A') {
-                Some(node.id.clone())
-            } else {
-                None
-            }
+        if difference % g as i128 != 0 {
+            return None;
+        }
+
+        let (_, x, _) = extended_gcd(self.modulus as i128, other.modulus as i128);
+        let step = (other.modulus / g) as i128;
+        let k = (difference / g as i128 * x).rem_euclid(step);
+
+        let modulus = self.modulus / g * other.modulus;
+        let residue = (self.residue as i128 + self.modulus as i128 * k).rem_euclid(modulus as i128);
+
+        Some(Progression {
+            residue: residue as usize,
+            modulus,
+            from: self.from.max(other.from),
         })
+    }
+
+    fn first(&self) -> usize {
+        self.from + (self.residue + self.modulus - self.from % self.modulus) % self.modulus
+    }
+}
+
+/// The first step on which every ghost is at an end node, if there is one.
+fn first_common_step(ghosts: &[GhostCycle]) -> Option<usize> {
+    // hits before a ghost's loop only happen once, so they can simply be tried
+    let one_off = ghosts
+        .iter()
+        .flat_map(|ghost| ghost.z_hits.iter().filter(|&&hit| hit < ghost.prefix))
+        .filter(|&&step| ghosts.iter().all(|ghost| ghost.hits(step)))
+        .copied();
+
+    let repeating = ghosts
+        .iter()
+        .fold(
+            vec![Progression {
+                residue: 0,
+                modulus: 1,
+                from: 0,
+            }],
+            |progressions, ghost| {
+                progressions
+                    .iter()
+                    .flat_map(|progression| {
+                        ghost
+                            .z_hits
+                            .iter()
+                            .filter(|&&hit| hit >= ghost.prefix)
+                            .filter_map(|&hit| {
+                                progression.intersect(&Progression {
+                                    residue: hit % ghost.cycle,
+                                    modulus: ghost.cycle,
+                                    from: hit,
+                                })
+                            })
+                    })
+                    .collect()
+            },
+        )
+        .into_iter()
+        .map(|progression| progression.first());
+
+    one_off.chain(repeating).min()
+}
+
+fn ghost_cycles(
+    directions: &Directions,
+    network: &Network,
+    is_start: impl Fn(&str) -> bool,
+    is_end: impl Fn(&str) -> bool,
+) -> Vec<GhostCycle> {
+    let start_points: Vec<NodeId> = (0..network.len())
+        .filter(|&id| is_start(network.name(id)))
         .collect();
 
-    println!("start points: {start_points:?}");
+    let start_names: Vec<&str> = start_points.iter().map(|&id| network.name(id)).collect();
+    println!("start points: {start_names:?}");
 
-    let steps: Vec<usize> =
-        start_points.iter()
-            .map(|start_point| {
-                count_steps(&node_map, directions, start_point)
-            })
-            .collect();
+    let is_end = network.select(is_end);
 
-    lcm(&steps)
+    start_points
+        .iter()
+        .map(|&start_point| GhostCycle::analyse(network, directions, start_point, &is_end))
+        .collect()
+}
+
+fn print_cycles(ghosts: &[GhostCycle], network: &Network) {
+    for ghost in ghosts {
+        println!(
+            "{}: prefix {}, cycle {} entering at ({}, {}), end nodes after {:?} steps",
+            network.name(ghost.start),
+            ghost.prefix,
+            ghost.cycle,
+            network.name(ghost.entry.0),
+            ghost.entry.1,
+            ghost.z_hits
+        );
+    }
+}
+
+fn part2(ghosts: &[GhostCycle], network: &Network) -> Result<usize, WalkError> {
+    if ghosts.is_empty() {
+        return Err(WalkError::NoGhosts);
+    }
+
+    if let Some(ghost) = ghosts.iter().find(|ghost| ghost.z_hits.is_empty()) {
+        return Err(WalkError::Unreachable {
+            start: network.name(ghost.start).to_string(),
+        });
+    }
+
+    first_common_step(ghosts).ok_or(WalkError::NoCommonStep)
+}
+
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let index = args.iter().position(|arg| arg == flag)?;
+
+    args.get(index + 1).map(String::as_str)
 }
 
 fn main() {
     let lines = read_input();
     let (directions, nodes) = parse(&lines);
+    let network = match Network::compile(&nodes) {
+        Ok(network) => network,
+        Err(err) => {
+            println!("error: {err}");
+            return;
+        }
+    };
+
+    let args: Vec<String> = std::env::args().collect();
+    let start = arg_value(&args, "--start").unwrap_or("AAA");
+    let target = arg_value(&args, "--target").unwrap_or("ZZZ");
+    let ghost_start = arg_value(&args, "--ghost-start").unwrap_or("A");
+    let ghost_end = arg_value(&args, "--ghost-end").unwrap_or("Z");
+
+    match part1(&directions, &network, start, |name| name == target) {
+        Ok(steps) => println!("part1: {steps}"),
+        Err(err) => println!("part1: {err}"),
+    }
+
+    let ghosts = ghost_cycles(
+        &directions,
+        &network,
+        |name| name.ends_with(ghost_start),
+        |name| name.ends_with(ghost_end),
+    );
+
+    if args.iter().any(|arg| arg == "--cycles") {
+        print_cycles(&ghosts, &network);
+    }
+
+    match part2(&ghosts, &network) {
+        Ok(steps) => println!("part2: {steps}"),
+        Err(err) => println!("part2: {err}"),
+    }
+
+    let dot_file = arg_value(&args, "--dot");
+    let json_file = arg_value(&args, "--json");
+
+    if dot_file.is_none() && json_file.is_none() {
+        return;
+    }
+
+    let path_directions = match arg_value(&args, "--directions").map(crate::directions) {
+        Some(Ok((_, path_directions))) => path_directions,
+        Some(Err(err)) => {
+            println!("error: {err:#?}");
+            return;
+        }
+        None => directions,
+    };
+
+    let highlight = match arg_value(&args, "--path") {
+        Some(path_start) => match network.id(path_start) {
+            Some(id) => Some(export::Walk::new(&network, &path_directions, id)),
+            None => {
+                println!("error: {}", WalkError::UnknownStart(path_start.to_string()));
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let annotations = export::Annotations::new(
+        &network,
+        &path_directions,
+        network.select(|name| name.ends_with(ghost_start)),
+        network.select(|name| name.ends_with(ghost_end)),
+        highlight,
+    );
+
+    let exports = [
+        (
+            dot_file,
+            export::to_dot as fn(&Network, &export::Annotations) -> String,
+        ),
+        (json_file, export::to_json),
+    ];
+
+    for (file, exporter) in exports {
+        if let Some(file) = file {
+            if let Err(err) = std::fs::write(file, exporter(&network, &annotations)) {
+                println!("error: writing {file}: {err}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    println!("part1: {}", part1(&directions, &nodes));
-    println!("part2: {}", part2(&directions, &nodes));
+    fn network(input: &str) -> (Directions, Network) {
+        let (directions, nodes) = parse(input);
+
+        (directions, Network::compile(&nodes).unwrap())
+    }
+
+    fn ghosts(directions: &Directions, network: &Network) -> Vec<GhostCycle> {
+        ghost_cycles(
+            directions,
+            network,
+            |name| name.ends_with('A'),
+            |name| name.ends_with('Z'),
+        )
+    }
+
+    /// Walk every ghost in lockstep until they are all at an end node at once.
+    fn brute_force(directions: &Directions, network: &Network, limit: usize) -> Option<usize> {
+        let mut current: Vec<NodeId> = (0..network.len())
+            .filter(|&id| network.name(id).ends_with('A'))
+            .collect();
+
+        for step in 0..limit {
+            if current.iter().all(|&id| network.name(id).ends_with('Z')) {
+                return Some(step);
+            }
+
+            let direction = &directions[step % directions.len()];
+            for id in &mut current {
+                *id = network.step(*id, direction);
+            }
+        }
+
+        None
+    }
+
+    #[test]
+    fn steps_to_the_target() {
+        let (directions, network) = network(
+            "RL\n\nAAA = (BBB, CCC)\nBBB = (DDD, EEE)\nCCC = (ZZZ, GGG)\nDDD = (DDD, DDD)\n\
+             EEE = (EEE, EEE)\nGGG = (GGG, GGG)\nZZZ = (ZZZ, ZZZ)",
+        );
+
+        assert_eq!(
+            part1(&directions, &network, "AAA", |name| name == "ZZZ").unwrap(),
+            2
+        );
+        assert_eq!(
+            part1(&directions, &network, "BBB", |name| name.starts_with('E')).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn unreachable_target() {
+        let (directions, network) =
+            network("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)");
+
+        assert!(matches!(
+            part1(&directions, &network, "AAA", |name| name == "ZZZ"),
+            Err(WalkError::Unreachable { start }) if start == "AAA"
+        ));
+    }
+
+    #[test]
+    fn unknown_start() {
+        let (directions, network) = network("L\n\nAAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)");
+
+        assert!(matches!(
+            part1(&directions, &network, "XYZ", |name| name == "ZZZ"),
+            Err(WalkError::UnknownStart(start)) if start == "XYZ"
+        ));
+    }
+
+    #[test]
+    fn link_to_a_missing_node() {
+        let (_, nodes) = parse("L\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)");
+
+        assert!(matches!(
+            Network::compile(&nodes),
+            Err(WalkError::MissingNode { node, neighbour }) if node == "AAA" && neighbour == "BBB"
+        ));
+    }
+
+    #[test]
+    fn end_node_before_the_cycle() {
+        let (directions, network) = network(
+            "L\n\n11A = (11Z, 11Z)\n11Z = (11B, 11B)\n11B = (11C, 11C)\n11C = (11B, 11B)\n\
+             22A = (22Z, 22Z)\n22Z = (22Z, 22Z)",
+        );
+        let ghosts = ghosts(&directions, &network);
+
+        assert_eq!((ghosts[0].prefix, ghosts[0].cycle), (2, 2));
+        assert_eq!(ghosts[0].z_hits, [1]);
+        assert_eq!(part2(&ghosts, &network).unwrap(), 1);
+        assert_eq!(brute_force(&directions, &network, 100), Some(1));
+    }
+
+    #[test]
+    fn two_end_nodes_in_one_cycle() {
+        let (directions, network) = network(
+            "L\n\n1AA = (1AZ, 1AZ)\n1AZ = (1BZ, 1BZ)\n1BZ = (1AB, 1AB)\n1AB = (1AZ, 1AZ)\n\
+             2AA = (2AB, 2AB)\n2AB = (2AC, 2AC)\n2AC = (2AD, 2AD)\n2AD = (2AZ, 2AZ)\n\
+             2AZ = (2AB, 2AB)",
+        );
+        let ghosts = ghosts(&directions, &network);
+
+        assert_eq!(ghosts[0].cycle, 3);
+        assert_eq!(ghosts[0].z_hits, [1, 2]);
+        assert_eq!(part2(&ghosts, &network).unwrap(), 4);
+        assert_eq!(brute_force(&directions, &network, 100), Some(4));
+    }
+
+    #[test]
+    fn cycles_that_never_line_up() {
+        let (directions, network) = network(
+            "L\n\n1AA = (1AB, 1AB)\n1AB = (1AZ, 1AZ)\n1AZ = (1AB, 1AB)\n\
+             2AA = (2AZ, 2AZ)\n2AZ = (2AB, 2AB)\n2AB = (2AC, 2AC)\n2AC = (2AD, 2AD)\n\
+             2AD = (2AZ, 2AZ)",
+        );
+        let ghosts = ghosts(&directions, &network);
+
+        assert_eq!((ghosts[0].cycle, ghosts[1].cycle), (2, 4));
+        assert!(matches!(
+            part2(&ghosts, &network),
+            Err(WalkError::NoCommonStep)
+        ));
+        assert_eq!(brute_force(&directions, &network, 100), None);
+    }
+
+    #[test]
+    fn no_ghosts() {
+        let (directions, network) = network("L\n\nBBB = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)");
+
+        assert!(matches!(
+            part2(&ghosts(&directions, &network), &network),
+            Err(WalkError::NoGhosts)
+        ));
+    }
 }
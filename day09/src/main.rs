@@ -1,3 +1,9 @@
+use std::{fmt, str::FromStr};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, Zero};
+
 fn read_input() -> Vec<String> {
     let input = include_str!("../input");
     input
@@ -6,62 +12,375 @@ fn read_input() -> Vec<String> {
         .collect()
 }
 
-type History = Vec<i32>;
-type Report = Vec<History>;
+/// The numbers a history can be made of. Every operation is checked, so a result that does not
+/// fit is reported instead of wrapping around.
+trait Reading:
+    Clone
+    + PartialEq
+    + Zero
+    + From<i64>
+    + Into<BigInt>
+    + FromStr
+    + fmt::Display
+    + CheckedAdd
+    + CheckedSub
+    + CheckedMul
+    + CheckedDiv
+{
+}
+
+impl<T> Reading for T where
+    T: Clone
+        + PartialEq
+        + Zero
+        + From<i64>
+        + Into<BigInt>
+        + FromStr
+        + fmt::Display
+        + CheckedAdd
+        + CheckedSub
+        + CheckedMul
+        + CheckedDiv
+{
+}
+
+#[derive(Debug)]
+struct Overflow;
 
-fn parse(lines: &[String]) -> Report {
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "overflow, try a wider number type")
+    }
+}
+
+type History<T> = Vec<T>;
+type Report<T> = Vec<History<T>>;
+
+fn parse<T: Reading>(lines: &[String]) -> Report<T> {
     lines.iter()
         .map(|line| {
             line.split_whitespace()
-                .map(|s| s.parse().unwrap())
+                .map(|s| s.parse().unwrap_or_else(|_| panic!("{s} is not a number")))
                 .collect()
         })
         .collect()
 }
 
-fn extrapolate(history: &History) -> i32 {
-    let mut endings = vec![];
-    let mut steps = history.clone();
+/// A history and its rows of differences, down to the first row that is all zero.
+struct DifferenceTable<T> {
+    rows: Vec<History<T>>,
+}
+
+impl<T: Reading> DifferenceTable<T> {
+    fn new(history: &History<T>) -> Result<DifferenceTable<T>, Overflow> {
+        let mut rows = vec![history.clone()];
+
+        loop {
+            let last = rows.last().unwrap();
+
+            if last.len() <= 1 || last.iter().all(Zero::is_zero) {
+                break;
+            }
+
+            let differences = last
+                .windows(2)
+                .map(|pair| pair[1].checked_sub(&pair[0]).ok_or(Overflow))
+                .collect::<Result<_, _>>()?;
+            rows.push(differences);
+        }
+
+        Ok(DifferenceTable { rows })
+    }
+
+    /// The value at `position`, where the first reading is at position 0. Uses Newton's forward
+    /// difference formula, `f(x) = sum over k of binomial(x, k) * Δᵏf(0)`, which is exact for
+    /// any integer position, negative ones included.
+    fn value_at(&self, position: i64) -> Result<T, Overflow> {
+        let mut binomial = T::from(1);
+        let mut value = T::zero();
+
+        for (k, row) in self.rows.iter().enumerate() {
+            let k = k as i64;
+
+            if k > 0 {
+                // binomial(x, k) = binomial(x, k - 1) * (x - k + 1) / k, which divides exactly
+                let factor = position
+                    .checked_sub(k)
+                    .and_then(|difference| difference.checked_add(1))
+                    .ok_or(Overflow)?;
+
+                binomial = binomial
+                    .checked_mul(&T::from(factor))
+                    .and_then(|product| product.checked_div(&T::from(k)))
+                    .ok_or(Overflow)?;
+            }
+
+            let term = binomial.checked_mul(&row[0]).ok_or(Overflow)?;
+            value = value.checked_add(&term).ok_or(Overflow)?;
+        }
 
-    let mut only_zero = false;
-    while !only_zero {
-        only_zero = true;
-        let last = *steps.last().unwrap();
-        endings.push(last);
+        Ok(value)
+    }
+
+    /// Whether differencing ended on a row of zeros. If it did not, the history is too short
+    /// to tell which polynomial it follows and any prediction is meaningless.
+    fn reaches_zero(&self) -> bool {
+        self.rows.last().unwrap().iter().all(Zero::is_zero)
+    }
+
+    /// The degree of the polynomial through the history, `None` for a history of only zeros or
+    /// one that never reaches a row of zeros.
+    fn degree(&self) -> Option<usize> {
+        if self.reaches_zero() {
+            self.rows.len().checked_sub(2)
+        } else {
+            None
+        }
+    }
+
+    /// Coefficients of the polynomial through the history, constant term first, in terms of the
+    /// position with the first reading at 0.
+    fn coefficients(&self) -> Vec<BigRational> {
+        let mut coefficients: Vec<BigRational> = vec![];
+        // x * (x - 1) * ... * (x - k + 1) / k!, lowest power first
+        let mut falling = vec![BigRational::one()];
+
+        for (k, row) in self.rows.iter().enumerate() {
+            if k > 0 {
+                let shift = BigRational::from_integer(BigInt::from(k as i64 - 1));
+                let divisor = BigRational::from_integer(BigInt::from(k as i64));
+
+                let mut next = vec![BigRational::zero(); falling.len() + 1];
+                for (power, coefficient) in falling.iter().enumerate() {
+                    next[power + 1] += coefficient / &divisor;
+                    next[power] -= coefficient * &shift / &divisor;
+                }
+                falling = next;
+            }
+
+            let leading = BigRational::from_integer(row[0].clone().into());
+
+            coefficients.resize(falling.len(), BigRational::zero());
+            for (power, coefficient) in falling.iter().enumerate() {
+                coefficients[power] += coefficient * &leading;
+            }
+        }
+
+        while coefficients.len() > 1 && coefficients.last().is_some_and(Zero::is_zero) {
+            coefficients.pop();
+        }
+
+        coefficients
+    }
+
+    /// The value `steps` readings after the last one, or before the first one if `steps` is
+    /// negative.
+    fn predict(&self, steps: i64) -> Result<T, Overflow> {
+        let len = self.rows[0].len() as i64;
+
+        if steps >= 0 {
+            self.value_at((len - 1).checked_add(steps).ok_or(Overflow)?)
+        } else {
+            self.value_at(steps)
+        }
+    }
+}
+
+impl<T: Reading> fmt::Display for DifferenceTable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (depth, row) in self.rows.iter().enumerate() {
+            let values: Vec<String> = row.iter().map(ToString::to_string).collect();
+
+            writeln!(f, "{}{}", " ".repeat(depth * 2), values.join("   "))?;
+        }
+
+        Ok(())
+    }
+}
 
-        let mut previous = steps[0];
-        (1..steps.len()).for_each(|i| {
-            let current = steps[i];
-            if current != 0 {
-                only_zero = false;
+fn print_fits<T: Reading>(report: &Report<T>) {
+    for (index, history) in report.iter().enumerate() {
+        let table = match DifferenceTable::new(history) {
+            Ok(table) => table,
+            Err(err) => {
+                println!("history {index}: {err}");
+                continue;
             }
-            steps[i - 1] = current - previous;
-            previous = current;
-        });
+        };
+
+        let coefficients: Vec<String> = table
+            .coefficients()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        match table.degree() {
+            Some(degree) => println!("history {index}: degree {degree}"),
+            None if table.reaches_zero() => println!("history {index}: all zeros"),
+            None => println!("history {index}: never reaches a row of zeros, not a polynomial"),
+        }
 
-        steps.pop();
+        println!("  coefficients: [{}]", coefficients.join(", "));
+        print!("{table}");
     }
+}
+
+fn predict_all<T: Reading>(report: &Report<T>, steps: i64) -> Result<T, Overflow> {
+    report.iter().try_fold(T::zero(), |total, history| {
+        let prediction = DifferenceTable::new(history)?.predict(steps)?;
+
+        total.checked_add(&prediction).ok_or(Overflow)
+    })
+}
 
-    endings.iter().sum()
+fn part1<T: Reading>(report: &Report<T>) -> Result<T, Overflow> {
+    predict_all(report, 1)
 }
 
-fn part1(report: &Report) -> i32 {
-    report.iter().map(extrapolate).sum()
+fn part2<T: Reading>(report: &Report<T>) -> Result<T, Overflow> {
+    predict_all(report, -1)
 }
 
-fn extrapolate_start(history: &History) -> i32 {
-    let history_reverse = history.iter().rev().copied().collect();
-    extrapolate(&history_reverse)
+fn print_result<T: Reading>(name: &str, result: Result<T, Overflow>) {
+    match result {
+        Ok(value) => println!("{name}: {value}"),
+        Err(err) => println!("{name}: {err}"),
+    }
 }
 
-fn part2(report: &Report) -> i32 {
-    report.iter().map(extrapolate_start).sum()
+fn run<T: Reading>(lines: &[String], args: &[String]) {
+    let parsed: Report<T> = parse(lines);
+
+    print_result("part1", part1(&parsed));
+    print_result("part2", part2(&parsed));
+
+    if args.iter().any(|arg| arg == "--fit") {
+        print_fits(&parsed);
+    }
+
+    if let Some(index) = args.iter().position(|arg| arg == "--predict") {
+        match args.get(index + 1).map(|steps| steps.parse::<i64>()) {
+            Some(Ok(steps)) => print_result(&format!("{steps} steps"), predict_all(&parsed, steps)),
+            _ => println!("usage: --predict <steps>"),
+        }
+    }
 }
 
 fn main() {
     let lines = read_input();
-    let parsed = parse(&lines);
+    let args: Vec<String> = std::env::args().collect();
+
+    let numbers = args
+        .iter()
+        .position(|arg| arg == "--numbers")
+        .and_then(|index| args.get(index + 1))
+        .map_or("i64", String::as_str);
+
+    match numbers {
+        "i64" => run::<i64>(&lines, &args),
+        "i128" => run::<i128>(&lines, &args),
+        "big" => run::<BigInt>(&lines, &args),
+        other => println!("unknown number type '{other}', expected i64, i128 or big"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    println!("part1: {}", part1(&parsed));
-    println!("part2: {}", part2(&parsed));
+    fn history<T: Reading>(readings: &[i64]) -> History<T> {
+        readings.iter().map(|&reading| T::from(reading)).collect()
+    }
+
+    const EXAMPLE: [[i64; 6]; 3] = [
+        [0, 3, 6, 9, 12, 15],
+        [1, 3, 6, 10, 15, 21],
+        [10, 13, 16, 21, 30, 45],
+    ];
+
+    #[test]
+    fn newton_matches_the_readings_and_the_example() {
+        let report: Report<i64> = EXAMPLE.iter().map(|readings| history(readings)).collect();
+
+        for history in &report {
+            let table = DifferenceTable::new(history).unwrap();
+
+            for (position, &reading) in history.iter().enumerate() {
+                assert_eq!(table.value_at(position as i64).unwrap(), reading);
+            }
+        }
+
+        assert_eq!(part1(&report).unwrap(), 114);
+        assert_eq!(part2(&report).unwrap(), 2);
+    }
+
+    #[test]
+    fn predictions_several_steps_away() {
+        let table = DifferenceTable::new(&history::<i64>(&[1, 4, 9, 16])).unwrap();
+
+        assert_eq!(table.predict(1).unwrap(), 25);
+        assert_eq!(table.predict(3).unwrap(), 49);
+        assert_eq!(table.predict(-1).unwrap(), 0);
+        assert_eq!(table.predict(-3).unwrap(), 4);
+    }
+
+    fn rationals(values: &[(i64, i64)]) -> Vec<BigRational> {
+        values
+            .iter()
+            .map(|&(numerator, denominator)| {
+                BigRational::new(BigInt::from(numerator), BigInt::from(denominator))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fit_of_a_quadratic() {
+        let table = DifferenceTable::new(&history::<i64>(&[1, 4, 9, 16])).unwrap();
+
+        assert!(table.reaches_zero());
+        assert_eq!(table.degree(), Some(2));
+        assert_eq!(table.coefficients(), rationals(&[(1, 1), (2, 1), (1, 1)]));
+
+        // the triangular numbers, x * (x + 1) / 2
+        let table = DifferenceTable::new(&history::<i64>(&[0, 1, 3, 6, 10])).unwrap();
+
+        assert_eq!(table.degree(), Some(2));
+        assert_eq!(table.coefficients(), rationals(&[(0, 1), (1, 2), (1, 2)]));
+    }
+
+    #[test]
+    fn fit_of_degenerate_histories() {
+        let zeros = DifferenceTable::new(&history::<i64>(&[0, 0, 0])).unwrap();
+
+        assert!(zeros.reaches_zero());
+        assert_eq!(zeros.degree(), None);
+
+        let too_short = DifferenceTable::new(&history::<i64>(&[1, 5])).unwrap();
+
+        assert!(!too_short.reaches_zero());
+        assert_eq!(too_short.degree(), None);
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        assert!(matches!(
+            DifferenceTable::new(&history::<i64>(&[i64::MIN, i64::MAX])),
+            Err(Overflow)
+        ));
+
+        let table = DifferenceTable::new(&history::<i64>(&[i64::MAX - 1, i64::MAX])).unwrap();
+        assert!(matches!(table.predict(1), Err(Overflow)));
+    }
+
+    #[test]
+    fn big_numbers_do_not_overflow() {
+        let table = DifferenceTable::new(&history::<BigInt>(&[i64::MIN, i64::MAX])).unwrap();
+        assert_eq!(
+            table.predict(1).unwrap(),
+            BigInt::from(i64::MAX) * 2 - BigInt::from(i64::MIN)
+        );
+
+        let table = DifferenceTable::new(&history::<BigInt>(&[i64::MAX - 1, i64::MAX])).unwrap();
+        assert_eq!(table.predict(1).unwrap(), BigInt::from(i64::MAX) + 1);
+    }
 }
@@ -1,11 +1,15 @@
-use std::{collections::{HashSet, VecDeque}, str::FromStr};
+use std::{collections::{HashSet, VecDeque}, fmt, str::FromStr};
+
+#[cfg(feature = "render")]
+mod render;
+mod svg;
 
 fn read_input() -> String {
     let input = include_str!("../input");
     input.to_string()
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Tile {
     VerticalPipe,
     HorizontalPipe,
@@ -37,15 +41,56 @@ impl FromStr for Tile {
     }
 }
 
+const PIPES: [Tile; 6] = [
+    Tile::VerticalPipe,
+    Tile::HorizontalPipe,
+    Tile::BendNorthEastPipe,
+    Tile::BendNorthWestPipe,
+    Tile::BendSouthWestPipe,
+    Tile::BendSouthEastPipe,
+];
+
+/// A map whose start tile has been replaced by the pipe it has to be.
 struct Map {
     tiles: Vec<Vec<Tile>>,
+    start: Coord,
+    main_loop: Vec<Coord>,
+}
+
+#[derive(Debug)]
+enum StartError {
+    Missing,
+    Several(Vec<Coord>),
+    /// Any of these pipes under the start would close a loop.
+    Ambiguous(Vec<Tile>),
+    /// Fewer than two neighbours connect to the start.
+    Impossible { connections: usize },
+    /// The pipes through the start run into a dead end instead of closing a loop.
+    NotOnLoop,
+}
+
+impl fmt::Display for StartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartError::Missing => write!(f, "the map has no start tile"),
+            StartError::Several(starts) => write!(f, "the map has several start tiles: {starts:?}"),
+            StartError::Ambiguous(pipes) => {
+                write!(f, "the start tile could be any of {pipes:?}")
+            }
+            StartError::Impossible { connections } => write!(
+                f,
+                "the start tile connects to {connections} pipes, it needs exactly 2"
+            ),
+            StartError::NotOnLoop => write!(f, "the start tile is not on a closed loop"),
+        }
+    }
 }
 
 impl FromStr for Map {
-    type Err = String;
+    type Err = StartError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tiles = s
+        let tiles: Vec<Vec<Tile>> = s
             .lines()
             .map(|line| {
                 line.chars()
@@ -54,12 +99,37 @@ impl FromStr for Map {
             })
             .collect();
 
-        Ok(Map { tiles })
+        let starts: Vec<Coord> = tiles
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, tile)| matches!(tile, Tile::Start))
+                    .map(move |(x, _)| (x.try_into().unwrap(), y.try_into().unwrap()))
+            })
+            .collect();
+
+        let start = match starts[..] {
+            [] => return Err(StartError::Missing),
+            [start] => start,
+            _ => return Err(StartError::Several(starts)),
+        };
+
+        let mut map = Map {
+            tiles,
+            start,
+            main_loop: vec![],
+        };
+
+        map.infer_start()?;
+
+        Ok(map)
     }
 }
 
-fn parse(input: &str) -> Map {
-    Map::from_str(input).unwrap()
+fn parse(input: &str) -> Result<Map, StartError> {
+    Map::from_str(input)
 }
 
 type Coord = (i32, i32);
@@ -78,23 +148,70 @@ impl Tile {
             Tile::BendNorthWestPipe => vec![north, west],
             Tile::BendSouthWestPipe => vec![south, west],
             Tile::BendSouthEastPipe => vec![south, east],
-            Tile::Start => vec![north, east, south, west],
+            Tile::Start => panic!("The start tile is replaced by a pipe after parsing"),
             Tile::Ground => panic!("Ground doesn't go anywhere"),
         }
     }
+
+    fn is_pipe(self) -> bool {
+        !matches!(self, Tile::Ground | Tile::Start)
+    }
 }
 
 impl Map {
     fn start(&self) -> Coord {
-        for y in 0..self.tiles.len() {
-            for x in 0..self.tiles[0].len() {
-                if let Tile::Start = self.tiles[y][x] {
-                    return (x.try_into().unwrap(), y.try_into().unwrap());
-                }
+        self.start
+    }
+
+    /// Put the pipe under the start tile that closes a loop through it, and take that loop as
+    /// the main loop. Neighbours that connect back but lead nowhere are only junk and are ignored.
+    fn infer_start(&mut self) -> Result<(), StartError> {
+        let (x, y) = self.start;
+
+        let connections: Vec<(i32, i32)> = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .into_iter()
+            .filter(|&displacement| self.connects_back((x, y), displacement).is_some())
+            .collect();
+
+        let candidates: Vec<Tile> = PIPES
+            .into_iter()
+            .filter(|pipe| {
+                pipe.displacements()
+                    .iter()
+                    .all(|displacement| connections.contains(displacement))
+            })
+            .collect();
+
+        if candidates.is_empty() {
+            return Err(StartError::Impossible {
+                connections: connections.len(),
+            });
+        }
+
+        let mut closing = vec![];
+
+        for pipe in candidates {
+            self.tiles[y as usize][x as usize] = pipe;
+
+            if let Some(main_loop) = self.walk_loop(self.start, &mut HashSet::new()) {
+                closing.push((pipe, main_loop));
             }
         }
 
-        unreachable!("There has to be a start");
+        match closing.len() {
+            0 => Err(StartError::NotOnLoop),
+            1 => {
+                let (pipe, main_loop) = closing.remove(0);
+
+                self.tiles[y as usize][x as usize] = pipe;
+                self.main_loop = main_loop;
+
+                Ok(())
+            }
+            _ => Err(StartError::Ambiguous(
+                closing.into_iter().map(|(pipe, _)| pipe).collect(),
+            )),
+        }
     }
 
     fn get(&self, (x, y): Coord) -> Option<Tile> {
@@ -108,115 +225,119 @@ impl Map {
         self.tiles.get(y).and_then(|row| row.get(x)).copied()
     }
 
-    // Find neighbours of a coord which are connected to the pipe at the coord.
-    fn connected(&self, coord @ (x, y): Coord) -> Vec<(Coord, Tile)> {
-        let tile = self.get(coord);
-        let tile = tile.expect("Non-existent tile is not connected to anything");
-
-        let displacements = tile.displacements();
-
-        if let Tile::Start = tile {
-            displacements
-                .iter()
-                .filter_map(|(dx, dy)| {
-                    let new_coord = (x + dx, y + dy);
-                    let neighbour = self.get(new_coord);
-
-                    // If the neighbour of a neighbour of start is start, it is connected to start.
-                    match neighbour {
-                        Some(Tile::Ground) | None => None,
-                        Some(neighbour) => {
-                            let is_connected_to_start = self
-                                .connected(new_coord)
-                                .iter()
-                                .any(|(_, tile)| matches!(tile, Tile::Start));
-
-                            if is_connected_to_start {
-                                Some((new_coord, neighbour))
-                            } else {
-                                None
-                            }
-                        }
-                    }
-                })
-                .collect()
-        } else {
-            displacements
-                .iter()
-                .map(|(dx, dy)| {
-                    let new_coord = (x + dx, y + dy);
-
-                    let neighbour = self.get(new_coord);
-
-                    (
-                        new_coord,
-                        neighbour
-                            .expect("All neighbours of a non-start pipe should be another pipe"),
-                    )
-                })
-                .collect()
+    /// The neighbour in the direction of `displacement`, if it is a pipe leading back to `coord`.
+    fn connects_back(&self, (x, y): Coord, (dx, dy): (i32, i32)) -> Option<(Coord, Tile)> {
+        let new_coord = (x + dx, y + dy);
+
+        match self.get(new_coord) {
+            Some(neighbour)
+                if neighbour.is_pipe() && neighbour.displacements().contains(&(-dx, -dy)) =>
+            {
+                Some((new_coord, neighbour))
+            }
+            _ => None,
         }
     }
 
-    fn next_in_loop(&self, coord: Coord, previous: Coord) -> (Coord, Tile) {
-        let connected = self.connected(coord);
-        let (first @ (first_coord, _), second) = (connected[0], connected[1]);
+    // Find neighbours of a coord which are connected to the pipe at the coord. Pipes that lead
+    // off the map, into ground or into a pipe facing elsewhere are not connected to anything.
+    fn connected(&self, coord: Coord) -> Vec<(Coord, Tile)> {
+        match self.get(coord) {
+            Some(tile) if tile.is_pipe() => tile
+                .displacements()
+                .into_iter()
+                .filter_map(|displacement| self.connects_back(coord, displacement))
+                .collect(),
+            _ => vec![],
+        }
+    }
 
-        if first_coord == previous {
-            second
-        } else {
-            first
+    fn next_in_loop(&self, coord: Coord, previous: Coord) -> Option<(Coord, Tile)> {
+        match self.connected(coord)[..] {
+            [first @ (first_coord, _), second] => {
+                Some(if first_coord == previous { second } else { first })
+            }
+            _ => None,
         }
     }
 
-    fn big_main_loop(&self) -> HashSet<Coord> {
-        let start_coord = self.start();
-        let mut main_loop = HashSet::new();
-        main_loop.insert(start_coord);
-
-        let connected = self.connected(start_coord);
-        let ((mut first_coord, _), (mut second_coord, _)) =
-            (connected[0], connected[1]);
-
-        let mut previous_first = start_coord;
-        let mut previous_second = start_coord;
-
-        while first_coord != second_coord {
-            main_loop.insert(first_coord);
-            main_loop.insert(second_coord);
-
-            let new_first = self.next_in_loop(first_coord, previous_first);
-            previous_first = first_coord;
-            (first_coord, _) = new_first;
-            let new_second = self.next_in_loop(second_coord, previous_second);
-            previous_second = second_coord;
-            (second_coord, _) = new_second;
+    /// Walk the pipes from `first` until they lead back to it, giving the loop in walking order.
+    /// `None` if the pipes run into a dead end or into a tile visited by an earlier walk.
+    fn walk_loop(&self, first: Coord, visited: &mut HashSet<Coord>) -> Option<Vec<Coord>> {
+        let mut tiles = vec![first];
+        visited.insert(first);
+
+        let (mut coord, _) = *self.connected(first).first()?;
+        let mut previous = first;
+
+        while coord != first {
+            // a tile seen before can only belong to a chain that was already found open
+            if !visited.insert(coord) {
+                return None;
+            }
+
+            tiles.push(coord);
+
+            let (next, _) = self.next_in_loop(coord, previous)?;
+            previous = coord;
+            coord = next;
         }
 
-        main_loop.insert(first_coord);
+        Some(tiles)
+    }
+
+    /// The tiles of the main loop in walking order, starting at the start tile.
+    fn main_loop(&self) -> &[Coord] {
+        &self.main_loop
+    }
+
+    /// Every closed loop of pipes on the map, the main loop included. Pipes that only form a
+    /// dangling chain are left out.
+    fn loops(&self) -> Vec<PipeLoop> {
+        let mut visited = HashSet::new();
+        let mut loops = vec![];
+
+        for y in 0..self.tiles.len() {
+            for x in 0..self.tiles[0].len() {
+                let first: Coord = (x.try_into().unwrap(), y.try_into().unwrap());
 
+                if visited.contains(&first) || self.connected(first).len() != 2 {
+                    continue;
+                }
+
+                if let Some(tiles) = self.walk_loop(first, &mut visited) {
+                    loops.push(PipeLoop {
+                        contains_start: tiles.contains(&self.start),
+                        tiles,
+                    });
+                }
+            }
+        }
+
+        loops
+    }
+
+    fn main_pipe_loop(&self, winding: Winding) -> PipeLoop {
+        PipeLoop {
+            tiles: self.main_loop().to_vec(),
+            contains_start: true,
+        }
+        .towards(winding)
+    }
+
+    fn big_main_loop(&self) -> HashSet<Coord> {
         let mut big_main_loop = HashSet::new();
 
-        for &(x, y) in &main_loop {
+        for &(x, y) in self.main_loop() {
             match self.get((x, y)) {
                 Some(tile) => {
                     // middle
                     big_main_loop.insert((x * 3 + 1, y * 3 + 1));
 
-                    if let Tile::Start = tile {
-                        let connected_to_start = self.connected((x, y));
-
-                        for &((nx, ny), _) in &connected_to_start {
-                            let (dx, dy) = (nx - x, ny - y);
+                    let displacements = tile.displacements();
 
-                            big_main_loop.insert((x * 3 + 1 + dx, y * 3 + 1 + dy));
-                        }
-                    } else {
-                        let displacements = tile.displacements();
-
-                        for &(dx, dy) in &displacements {
-                            big_main_loop.insert((x * 3 + 1 + dx, y * 3 + 1 + dy));
-                        }
+                    for &(dx, dy) in &displacements {
+                        big_main_loop.insert((x * 3 + 1 + dx, y * 3 + 1 + dy));
                     }
                 }
                 None => unreachable!("Tiles in main loop should exist."),
@@ -225,46 +346,115 @@ impl Map {
 
         big_main_loop
     }
+
+    /// The largest coordinates of the 3× expanded map.
+    fn big_bounds(&self) -> Coord {
+        let x_max = self.tiles[0].len();
+        let x_max: i32 = (x_max * 3 - 1).try_into().unwrap();
+        let y_max = self.tiles.len();
+        let y_max: i32 = (y_max * 3 - 1).try_into().unwrap();
+
+        (x_max, y_max)
+    }
 }
 
-fn part1(map: &Map) -> u32 {
-    let start_coord = map.start();
+/// A closed loop of pipes, its tiles in walking order.
+struct PipeLoop {
+    tiles: Vec<Coord>,
+    contains_start: bool,
+}
 
-    let connected = map.connected(start_coord);
-    let ((mut first_coord, _), (mut second_coord, _)) =
-        (connected[0], connected[1]);
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Winding {
+    /// Clockwise as the map is drawn, with y growing downwards.
+    Clockwise,
+    Anticlockwise,
+}
 
-    let mut previous_first = start_coord;
-    let mut previous_second = start_coord;
+impl PipeLoop {
+    fn len(&self) -> usize {
+        self.tiles.len()
+    }
 
-    let mut distance = 1;
-    while first_coord != second_coord {
-        distance += 1;
+    fn winding(&self) -> Winding {
+        let double_area: i64 = self
+            .tiles
+            .iter()
+            .zip(self.tiles.iter().cycle().skip(1))
+            .map(|(&(x1, y1), &(x2, y2))| {
+                i64::from(x1) * i64::from(y2) - i64::from(x2) * i64::from(y1)
+            })
+            .sum();
 
-        let new_first = map.next_in_loop(first_coord, previous_first);
-        previous_first = first_coord;
-        (first_coord, _) = new_first;
-        let new_second = map.next_in_loop(second_coord, previous_second);
-        previous_second = second_coord;
-        (second_coord, _) = new_second;
+        if double_area > 0 {
+            Winding::Clockwise
+        } else {
+            Winding::Anticlockwise
+        }
     }
 
-    distance
+    /// The same loop walked the other way round if needed, still from the same first tile.
+    fn towards(mut self, winding: Winding) -> PipeLoop {
+        if self.winding() != winding {
+            self.tiles[1..].reverse();
+        }
+
+        self
+    }
+
+    fn position(&self, coord: Coord) -> Option<usize> {
+        self.tiles.iter().position(|&tile| tile == coord)
+    }
+
+    /// The tile furthest along the loop from the first tile, whichever way round you go.
+    fn farthest(&self) -> Coord {
+        self.tiles[self.len() / 2]
+    }
+
+    /// The number of steps between two tiles of the loop, going the shorter way round.
+    fn distance(&self, from: Coord, to: Coord) -> Option<usize> {
+        let steps = self.position(from)?.abs_diff(self.position(to)?);
+
+        Some(steps.min(self.len() - steps))
+    }
+
+    /// The tile `offset` steps from the first tile, going backwards for negative offsets.
+    fn tile_at(&self, offset: i64) -> Coord {
+        let len: i64 = self.len().try_into().unwrap();
+        let index: usize = offset.rem_euclid(len).try_into().unwrap();
+
+        self.tiles[index]
+    }
 }
 
-fn part2(map: &Map) -> usize {
-    let big_main_loop = map.big_main_loop();
+fn print_loops(map: &Map) {
+    let loops = map.loops();
+
+    println!("{} closed loops", loops.len());
+
+    for pipe_loop in &loops {
+        println!(
+            "  {} tiles from {:?}{}",
+            pipe_loop.len(),
+            pipe_loop.tiles[0],
+            if pipe_loop.contains_start { ", contains S" } else { "" }
+        );
+    }
+}
 
-    let mut outside = HashSet::new();
+fn part1(map: &Map) -> usize {
+    map.main_pipe_loop(Winding::Clockwise).len() / 2
+}
+
+/// Every outside coord of the 3× expanded map, in the order the flood fill reaches them.
+fn flood_outside(map: &Map, big_main_loop: &HashSet<Coord>) -> Vec<Coord> {
+    let mut outside = vec![];
     let mut checked = HashSet::new();
 
     let mut queue = VecDeque::from([(0, 0)]);
 
     let displacements = [ (0, 1), (0, -1), (1, 0), (-1, 0) ];
-    let x_max = map.tiles[0].len();
-    let x_max: i32 = (x_max * 3 - 1).try_into().unwrap();
-    let y_max = map.tiles.len();
-    let y_max: i32 = (y_max * 3 - 1).try_into().unwrap();
+    let (x_max, y_max) = map.big_bounds();
 
     // BFS for all outside tiles
     while !queue.is_empty() {
@@ -277,7 +467,7 @@ fn part2(map: &Map) -> usize {
         checked.insert(n);
 
         if !big_main_loop.contains(&n) {
-            outside.insert(n);
+            outside.push(n);
 
             for (dx, dy) in displacements {
                 let (nx, ny) = (x + dx, y + dy);
@@ -291,35 +481,266 @@ fn part2(map: &Map) -> usize {
         }
     }
 
-    let inside: HashSet<_> =
-        (0..=x_max).flat_map(|x| {
-            (0..=y_max).filter_map(|y| {
-                let coord = (x, y);
-                if !outside.contains(&coord) && !big_main_loop.contains(&coord) {
-                    Some(coord)
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>()
+    outside
+}
+
+/// The coords of the 3× expanded map that are neither outside nor part of the main loop.
+fn inside(map: &Map, big_main_loop: &HashSet<Coord>, outside: &HashSet<Coord>) -> HashSet<Coord> {
+    let (x_max, y_max) = map.big_bounds();
+
+    (0..=x_max).flat_map(|x| {
+        (0..=y_max).filter_map(|y| {
+            let coord = (x, y);
+            if !outside.contains(&coord) && !big_main_loop.contains(&coord) {
+                Some(coord)
+            } else {
+                None
+            }
         })
-        .collect();
+        .collect::<Vec<_>>()
+    })
+    .collect()
+}
 
+/// The tiles of the map, not part of the main loop, that the main loop encloses.
+fn enclosed(map: &Map) -> HashSet<Coord> {
+    let big_main_loop = map.big_main_loop();
+    let outside = flood_outside(map, &big_main_loop).into_iter().collect();
+    let inside = inside(map, &big_main_loop, &outside);
+
+    // the middle of a tile off the loop is never next to the loop in the expanded map
     inside.iter()
-        .filter(|&(x, y)| {
-            let displacements = [ (0, 1), (0, -1), (1, 0), (-1, 0), (1, 1), (-1, -1), (-1, 1), (1, -1) ];
-            !displacements.iter().any(|&(dx, dy)| {
-                let new = (x + dx, y + dy);
-                big_main_loop.contains(&new)
-            })
+        .filter(|&(x, y)| x % 3 == 1 && y % 3 == 1)
+        .map(|&(x, y)| (x / 3, y / 3))
+        .collect()
+}
+
+fn part2(map: &Map) -> usize {
+    enclosed(map).len()
+}
+
+/// Count the tiles enclosed by a loop without looking at the rest of the map. The shoelace
+/// formula gives the area of the polygon through the tile centres, and Pick's theorem,
+/// `area = inside + boundary / 2 - 1`, turns that into the number of tiles inside.
+fn enclosed_tiles(main_loop: &[Coord]) -> usize {
+    let double_area: i64 = main_loop
+        .iter()
+        .zip(main_loop.iter().cycle().skip(1))
+        .map(|(&(x1, y1), &(x2, y2))| {
+            i64::from(x1) * i64::from(y2) - i64::from(x2) * i64::from(y1)
         })
-        .count() / 9
+        .sum::<i64>()
+        .abs();
+
+    let boundary = main_loop.len() as i64;
+
+    ((double_area - boundary + 2) / 2).try_into().unwrap()
+}
+
+fn part2_shoelace(map: &Map) -> usize {
+    enclosed_tiles(map.main_loop())
+}
+
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let index = args.iter().position(|arg| arg == flag)?;
+
+    args.get(index + 1).map(String::as_str)
+}
+
+fn parse_coord(coord: &str) -> Option<Coord> {
+    let (x, y) = coord.split_once(',')?;
+
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+fn print_queries(map: &Map, args: &[String]) {
+    let winding = if args.iter().any(|arg| arg == "--anticlockwise") {
+        Winding::Anticlockwise
+    } else {
+        Winding::Clockwise
+    };
+
+    let main_loop = map.main_pipe_loop(winding);
+
+    if args.iter().any(|arg| arg == "--farthest") {
+        let farthest = main_loop.farthest();
+
+        println!(
+            "farthest tile: {farthest:?}, {} steps from the start",
+            main_loop.distance(map.start(), farthest).unwrap()
+        );
+    }
+
+    if let Some(index) = args.iter().position(|arg| arg == "--distance") {
+        let from = args.get(index + 1).and_then(|coord| parse_coord(coord));
+        let to = args.get(index + 2).and_then(|coord| parse_coord(coord));
+
+        match (from, to) {
+            (Some(from), Some(to)) => match main_loop.distance(from, to) {
+                Some(steps) => println!("distance from {from:?} to {to:?}: {steps}"),
+                None => println!("error: {from:?} and {to:?} are not both on the main loop"),
+            },
+            _ => println!("error: --distance takes two coords written as x,y"),
+        }
+    }
+
+    if let Some(offset) = arg_value(args, "--along") {
+        match offset.parse() {
+            Ok(offset) => println!("{offset} steps along the loop: {:?}", main_loop.tile_at(offset)),
+            Err(err) => println!("error: offset {offset}: {err}"),
+        }
+    }
 }
 
 fn main() {
     let input = read_input();
-    let parsed = parse(&input);
+    let parsed = match parse(&input) {
+        Ok(map) => map,
+        Err(err) => {
+            println!("error: {err}");
+            return;
+        }
+    };
 
     println!("part1: {}", part1(&parsed));
-    println!("part2: {}", part2(&parsed));
+    if std::env::args().any(|arg| arg == "--shoelace") {
+        println!("part2: {}", part2_shoelace(&parsed));
+    } else {
+        println!("part2: {}", part2(&parsed));
+    }
+
+    #[cfg(feature = "render")]
+    render::run(&parsed);
+
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.iter().any(|arg| arg == "--loops") {
+        print_loops(&parsed);
+    }
+
+    print_queries(&parsed, &args);
+
+    if let Some(file) = arg_value(&args, "--svg") {
+        if let Err(err) = std::fs::write(file, svg::to_svg(&parsed)) {
+            println!("error: writing {file}: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_on_a_dangling_chain_is_rejected() {
+        let map = Map::from_str(".S-..\n.|...\n");
+
+        assert!(matches!(map, Err(StartError::NotOnLoop)));
+    }
+
+    #[test]
+    fn start_errors() {
+        assert!(matches!(Map::from_str("...\n...\n"), Err(StartError::Missing)));
+        assert!(matches!(
+            Map::from_str("S.S\n...\n"),
+            Err(StartError::Several(starts)) if starts == [(0, 0), (2, 0)]
+        ));
+        assert!(matches!(
+            Map::from_str("...\n.S-\n...\n"),
+            Err(StartError::Impossible { connections: 1 })
+        ));
+        assert!(matches!(
+            Map::from_str("..F7.\n..||.\n.FSJ.\n.LJ..\n"),
+            Err(StartError::Ambiguous(pipes))
+                if pipes == [Tile::BendNorthEastPipe, Tile::BendSouthWestPipe]
+        ));
+    }
+
+    #[test]
+    fn junk_next_to_the_start_is_ignored() {
+        let map = Map::from_str(".|...\n.S-7.\n.|.|.\n.L-J.\n").unwrap();
+
+        assert_eq!(map.get(map.start()), Some(Tile::BendSouthEastPipe));
+        assert_eq!(part1(&map), 4);
+    }
+
+    #[test]
+    fn junk_pipes_do_not_break_the_loops() {
+        let map = Map::from_str("|.F7-\n.FJ|.\nSJ.L7\n|F--J\nLJF7.\n..LJ.\n").unwrap();
+
+        let loops: Vec<(usize, bool)> = map
+            .loops()
+            .iter()
+            .map(|pipe_loop| (pipe_loop.len(), pipe_loop.contains_start))
+            .collect();
+
+        assert_eq!(loops, [(16, true), (4, false)]);
+        assert_eq!(part1(&map), 8);
+    }
+
+    #[test]
+    fn queries_along_the_square_loop() {
+        let map = Map::from_str(".....\n.S-7.\n.|.|.\n.L-J.\n.....\n").unwrap();
+
+        let clockwise = map.main_pipe_loop(Winding::Clockwise);
+        assert_eq!(clockwise.winding(), Winding::Clockwise);
+        assert_eq!(
+            clockwise.tiles,
+            [(1, 1), (2, 1), (3, 1), (3, 2), (3, 3), (2, 3), (1, 3), (1, 2)]
+        );
+        assert_eq!(clockwise.farthest(), (3, 3));
+        assert_eq!(clockwise.distance((1, 1), (3, 3)), Some(4));
+        // the short way round passes the start
+        assert_eq!(clockwise.distance((2, 1), (1, 2)), Some(2));
+        assert_eq!(clockwise.distance((2, 1), (0, 0)), None);
+        assert_eq!(clockwise.tile_at(1), (2, 1));
+        assert_eq!(clockwise.tile_at(-1), (1, 2));
+        assert_eq!(clockwise.tile_at(-10), (1, 3));
+
+        let anticlockwise = map.main_pipe_loop(Winding::Anticlockwise);
+        assert_eq!(anticlockwise.winding(), Winding::Anticlockwise);
+        assert_eq!(anticlockwise.tiles[0], map.start());
+        assert_eq!(anticlockwise.farthest(), (3, 3));
+        assert_eq!(anticlockwise.distance((2, 1), (1, 2)), Some(2));
+        assert_eq!(anticlockwise.tile_at(1), (1, 2));
+        assert_eq!(anticlockwise.tile_at(-1), (2, 1));
+        assert_eq!(anticlockwise.tile_at(-10), (3, 1));
+    }
+
+    const EXAMPLES: [(&str, usize); 4] = [
+        (
+            "...........\n.S-------7.\n.|F-----7|.\n.||.....||.\n.||.....||.\n\
+             .|L-7.F-J|.\n.|..|.|..|.\n.L--J.L--J.\n...........\n",
+            4,
+        ),
+        (
+            "..........\n.S------7.\n.|F----7|.\n.||....||.\n.||....||.\n\
+             .|L-7F-J|.\n.|..||..|.\n.L--JL--J.\n..........\n",
+            4,
+        ),
+        (
+            ".F----7F7F7F7F-7....\n.|F--7||||||||FJ....\n.||.FJ||||||||L7....\n\
+             FJL7L7LJLJ||LJ.L-7..\nL--J.L7...LJS7F-7L7.\n....F-J..F7FJ|L7L7L7\n\
+             ....L7.F7||L7|.L7L7|\n.....|FJLJ|FJ|F7|.LJ\n....FJL-7.||.||||...\n\
+             ....L---J.LJ.LJLJ...\n",
+            8,
+        ),
+        (
+            "FF7FSF7F7F7F7F7F---7\nL|LJ||||||||||||F--J\nFL-7LJLJ||||||LJL-77\n\
+             F--JF--7||LJLJ7F7FJ-\nL---JF-JLJ.||-FJLJJ7\n|F|F-JF---7F7-L7L|7|\n\
+             |FFJF7L7F-JF7|JL---7\n7-L-JL7||F7|L7F-7F7|\nL.L7LFJ|||||FJL7||LJ\n\
+             L7JLJL-JLJLJL--JLJ.L\n",
+            10,
+        ),
+    ];
+
+    #[test]
+    fn shoelace_agrees_with_flood_fill() {
+        for (input, enclosed) in EXAMPLES {
+            let map = Map::from_str(input).unwrap();
+
+            assert_eq!(part2(&map), enclosed);
+            assert_eq!(part2_shoelace(&map), enclosed);
+        }
+    }
 }
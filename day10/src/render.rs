@@ -0,0 +1,185 @@
+use std::{collections::HashSet, fmt::Write, thread, time::Duration};
+
+use colored::{ColoredString, Colorize};
+
+use crate::{enclosed, flood_outside, inside, Coord, Map, Tile};
+
+impl Tile {
+    fn symbol(self) -> char {
+        match self {
+            Tile::VerticalPipe => '│',
+            Tile::HorizontalPipe => '─',
+            Tile::BendNorthEastPipe => '└',
+            Tile::BendNorthWestPipe => '┘',
+            Tile::BendSouthWestPipe => '┐',
+            Tile::BendSouthEastPipe => '┌',
+            Tile::Ground => '.',
+            Tile::Start => 'S',
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Region {
+    Loop,
+    Inside,
+    Outside,
+    /// Not reached by the flood fill yet.
+    Unknown,
+}
+
+fn paint(symbol: char, region: Region) -> ColoredString {
+    let symbol = symbol.to_string();
+
+    match region {
+        Region::Loop => symbol.blue().bold(),
+        Region::Inside => symbol.yellow(),
+        Region::Outside => symbol.red(),
+        Region::Unknown => symbol.normal(),
+    }
+}
+
+/// The character at a coord of the 3× expanded map. Every pipe runs through the middle of its
+/// 3×3 block and out towards the neighbours it connects to.
+fn big_symbol(map: &Map, (x, y): Coord) -> char {
+    let tile = map
+        .get((x / 3, y / 3))
+        .expect("Coord should be on the expanded map");
+
+    if !tile.is_pipe() {
+        return '.';
+    }
+
+    let offset = (x % 3 - 1, y % 3 - 1);
+
+    match offset {
+        (0, 0) => tile.symbol(),
+        (0, _) if tile.displacements().contains(&offset) => '│',
+        (_, 0) if tile.displacements().contains(&offset) => '─',
+        _ => '.',
+    }
+}
+
+/// The map with the main loop highlighted and every other tile coloured by whether the loop
+/// encloses it.
+pub fn draw(map: &Map) -> String {
+    let enclosed = enclosed(map);
+    let main_loop: HashSet<Coord> = map.main_loop().iter().copied().collect();
+
+    let mut output = String::new();
+
+    for (y, row) in map.tiles.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            let coord: Coord = (x.try_into().unwrap(), y.try_into().unwrap());
+
+            let region = if main_loop.contains(&coord) {
+                Region::Loop
+            } else if enclosed.contains(&coord) {
+                Region::Inside
+            } else {
+                Region::Outside
+            };
+
+            let symbol = if coord == map.start() {
+                'S'
+            } else {
+                tile.symbol()
+            };
+
+            write!(output, "{}", paint(symbol, region)).unwrap();
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
+/// The 3× expanded map the flood fill runs on, coloured by the regions found so far.
+pub fn draw_big(
+    map: &Map,
+    big_main_loop: &HashSet<Coord>,
+    outside: &HashSet<Coord>,
+    inside: &HashSet<Coord>,
+) -> String {
+    let (x_max, y_max) = map.big_bounds();
+    let (start_x, start_y) = map.start();
+
+    let mut output = String::new();
+
+    for y in 0..=y_max {
+        for x in 0..=x_max {
+            let coord = (x, y);
+
+            let region = if big_main_loop.contains(&coord) {
+                Region::Loop
+            } else if outside.contains(&coord) {
+                Region::Outside
+            } else if inside.contains(&coord) {
+                Region::Inside
+            } else {
+                Region::Unknown
+            };
+
+            let symbol = if coord == (start_x * 3 + 1, start_y * 3 + 1) {
+                'S'
+            } else {
+                big_symbol(map, coord)
+            };
+
+            write!(output, "{}", paint(symbol, region)).unwrap();
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
+fn show_frame(frame: &str) {
+    // clear the screen and move the cursor to the top left first
+    print!("\x1b[2J\x1b[H{frame}");
+}
+
+/// Play the flood fill of the outside in the terminal, split over roughly `frames` frames, and
+/// finish on the fully coloured map.
+pub fn animate(map: &Map, frames: usize, delay: Duration) {
+    let big_main_loop = map.big_main_loop();
+    let order = flood_outside(map, &big_main_loop);
+    let per_frame = order.len().div_ceil(frames.max(1)).max(1);
+
+    let mut outside = HashSet::new();
+    let no_inside = HashSet::new();
+
+    for chunk in order.chunks(per_frame) {
+        outside.extend(chunk.iter().copied());
+
+        show_frame(&draw_big(map, &big_main_loop, &outside, &no_inside));
+        thread::sleep(delay);
+    }
+
+    let inside = inside(map, &big_main_loop, &outside);
+    show_frame(&draw_big(map, &big_main_loop, &outside, &inside));
+}
+
+pub fn run(map: &Map) {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.iter().any(|arg| arg == "--draw") {
+        print!("{}", draw(map));
+    }
+
+    if args.iter().any(|arg| arg == "--draw-big") {
+        let big_main_loop = map.big_main_loop();
+        let outside = flood_outside(map, &big_main_loop).into_iter().collect();
+        let inside = inside(map, &big_main_loop, &outside);
+
+        print!("{}", draw_big(map, &big_main_loop, &outside, &inside));
+    }
+
+    if let Some(index) = args.iter().position(|arg| arg == "--animate") {
+        let frames = args.get(index + 1).and_then(|frames| frames.parse().ok());
+
+        animate(map, frames.unwrap_or(60), Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,101 @@
+use std::{collections::HashSet, fmt::Write};
+
+use crate::{enclosed, Coord, Map};
+
+/// Side of a tile in SVG user units.
+const TILE: i32 = 10;
+
+/// The path of a pipe: from the edge of its tile it leaves by, through the middle, to the edge of
+/// the other tile it connects to.
+fn pipe_path((x, y): Coord, displacements: &[(i32, i32)]) -> String {
+    let middle = (x * TILE + TILE / 2, y * TILE + TILE / 2);
+    let edge = |(dx, dy): (i32, i32)| (middle.0 + dx * TILE / 2, middle.1 + dy * TILE / 2);
+
+    let (from, to) = (edge(displacements[0]), edge(displacements[1]));
+
+    format!(
+        "M{} {} L{} {} L{} {}",
+        from.0, from.1, middle.0, middle.1, to.0, to.1
+    )
+}
+
+/// The whole map as an SVG image: the main loop stands out from the other pipes, the tiles it
+/// encloses are filled and the start tile is circled.
+pub fn to_svg(map: &Map) -> String {
+    let width = map.tiles[0].len() as i32 * TILE;
+    let height = map.tiles.len() as i32 * TILE;
+
+    let main_loop: HashSet<Coord> = map.main_loop().iter().copied().collect();
+
+    let mut svg = String::new();
+
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {width} {height}\" width=\"{width}\" height=\"{height}\">"
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "  <rect width=\"{width}\" height=\"{height}\" fill=\"white\"/>"
+    )
+    .unwrap();
+
+    let mut enclosed: Vec<Coord> = enclosed(map).into_iter().collect();
+    enclosed.sort_by_key(|&(x, y)| (y, x));
+
+    writeln!(svg, "  <g fill=\"gold\">").unwrap();
+    for (x, y) in enclosed {
+        writeln!(
+            svg,
+            "    <rect x=\"{}\" y=\"{}\" width=\"{TILE}\" height=\"{TILE}\"/>",
+            x * TILE,
+            y * TILE
+        )
+        .unwrap();
+    }
+    writeln!(svg, "  </g>").unwrap();
+
+    for (on_loop, group) in [
+        (
+            false,
+            "<g fill=\"none\" stroke=\"lightgrey\" stroke-width=\"1\">",
+        ),
+        (
+            true,
+            "<g fill=\"none\" stroke=\"blue\" stroke-width=\"3\" stroke-linejoin=\"round\">",
+        ),
+    ] {
+        writeln!(svg, "  {group}").unwrap();
+
+        for (y, row) in map.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let coord: Coord = (x.try_into().unwrap(), y.try_into().unwrap());
+
+                if tile.is_pipe() && main_loop.contains(&coord) == on_loop {
+                    writeln!(
+                        svg,
+                        "    <path d=\"{}\"/>",
+                        pipe_path(coord, &tile.displacements())
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        writeln!(svg, "  </g>").unwrap();
+    }
+
+    let (start_x, start_y) = map.start();
+    writeln!(
+        svg,
+        "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"red\"/>",
+        start_x * TILE + TILE / 2,
+        start_y * TILE + TILE / 2,
+        TILE / 3
+    )
+    .unwrap();
+
+    writeln!(svg, "</svg>").unwrap();
+
+    svg
+}